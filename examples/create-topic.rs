@@ -0,0 +1,53 @@
+extern crate unterflow_protocol;
+
+use std::env;
+use std::net::TcpStream;
+use unterflow_protocol::{RequestResponseMessage, TransportMessage};
+use unterflow_protocol::io::{FromBytes, FromData, ToBytes};
+use unterflow_protocol::message::{PartitionsRequest, PartitionsResponse, TopicEvent};
+use unterflow_protocol::sbe::{ControlMessageType, ExecuteCommandRequest};
+
+fn request(stream: &mut TcpStream, request_id: u64, message: RequestResponseMessage) -> RequestResponseMessage {
+    let request = match message {
+        RequestResponseMessage::ExecuteCommandRequest(message) => TransportMessage::request(request_id, message),
+        RequestResponseMessage::ControlMessageRequest(message) => TransportMessage::request(request_id, message),
+        message => panic!("Unexpected request message {:?}", message),
+    };
+    request.to_bytes(stream).expect("Failed to send request");
+
+    match TransportMessage::from_bytes(stream).expect("Failed to read response") {
+        TransportMessage::RequestResponse(response) => response.message,
+        response => panic!("Unexpected response {:?}", response),
+    }
+}
+
+fn main() {
+    let broker_address = env::args().nth(1).unwrap_or_else(
+        || "localhost:51015".to_string(),
+    );
+    let topic_name = env::args().nth(2).unwrap_or_else(|| "default-topic".to_string());
+    let partitions = env::args().nth(3).map_or(1, |p| p.parse().expect("Invalid number of partitions"));
+
+    let mut stream = TcpStream::connect(&broker_address).unwrap_or_else(|_| panic!("Failed to connect to broker {}", broker_address));
+    println!("Connected to broker {}", broker_address);
+
+    let message = ExecuteCommandRequest::create_topic(topic_name, partitions, 1).expect("Failed to create message");
+    match request(&mut stream, 1, message.into()) {
+        RequestResponseMessage::ExecuteCommandResponse(ref message) => {
+            let topic = TopicEvent::from_data(message).expect("Failed to read topic event");
+            println!("{:#?}", topic);
+        }
+        message => panic!("Unexpected response message {:?}", message),
+    }
+
+    let message = ControlMessageType::RequestPartitions
+        .with(&PartitionsRequest {})
+        .expect("Failed to create message");
+    match request(&mut stream, 2, message.into()) {
+        RequestResponseMessage::ControlMessageResponse(ref message) => {
+            let partitions = PartitionsResponse::from_data(message).expect("Failed to read partitions");
+            println!("{:#?}", partitions);
+        }
+        message => panic!("Unexpected response message {:?}", message),
+    }
+}
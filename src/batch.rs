@@ -0,0 +1,287 @@
+use TransportMessage;
+use alloc::vec::Vec;
+use io::{self, FromReader, Read, ToWriter, VectoredWriter, Write};
+
+/// Consecutive frames which are processed as one unit, the first frame is flagged as batch begin
+/// and the last as batch end.
+#[derive(Debug)]
+pub struct FrameBatch {
+    messages: Vec<TransportMessage>,
+}
+
+impl FrameBatch {
+    /// Creates a batch of the messages and flags their frames accordingly.
+    pub fn new<I: IntoIterator<Item = TransportMessage>>(messages: I) -> Self {
+        let mut messages: Vec<_> = messages.into_iter().collect();
+        let last = messages.len().saturating_sub(1);
+        for (idx, message) in messages.iter_mut().enumerate() {
+            let frame_header = message.frame_header_mut();
+            frame_header.set_batch_begin(idx == 0);
+            frame_header.set_batch_end(idx == last);
+        }
+
+        FrameBatch { messages }
+    }
+
+    pub fn messages(&self) -> &[TransportMessage] {
+        &self.messages
+    }
+
+    pub fn into_messages(self) -> Vec<TransportMessage> {
+        self.messages
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub fn write_to_vec(&self, buffer: &mut Vec<u8>) -> Result<(), io::Error> {
+        for message in &self.messages {
+            message.write_to_vec(buffer)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ToWriter for FrameBatch {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), io::Error> {
+        for message in &self.messages {
+            message.to_writer(writer)?;
+        }
+
+        Ok(())
+    }
+
+    fn to_writer_vectored<'a>(&'a self, writer: &mut VectoredWriter<'a>) -> Result<(), io::Error> {
+        for message in &self.messages {
+            message.to_writer_vectored(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromReader for FrameBatch {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, io::Error> {
+        let mut assembler = BatchAssembler::new();
+        loop {
+            let message = TransportMessage::from_reader(reader)?;
+            if let Some(batch) = assembler.push(message)? {
+                return Ok(batch);
+            }
+        }
+    }
+}
+
+/// Groups received frames into batches, frames outside of a batch are returned as a batch of a
+/// single message.
+#[derive(Debug, Default)]
+pub struct BatchAssembler {
+    pending: Option<Vec<TransportMessage>>,
+}
+
+impl BatchAssembler {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Number of messages of the incomplete batch.
+    pub fn pending(&self) -> usize {
+        self.pending.as_ref().map_or(0, Vec::len)
+    }
+
+    /// Adds the next received message and returns the batch it completes. An invalid sequence of
+    /// flags discards the incomplete batch, a begin frame still starts the next batch.
+    pub fn push(&mut self, message: TransportMessage) -> Result<Option<FrameBatch>, io::Error> {
+        let (begin, end) = {
+            let frame_header = message.frame_header();
+            (frame_header.is_batch_begin(), frame_header.is_batch_end())
+        };
+
+        match self.pending.take() {
+            None if begin && !end => {
+                self.pending = Some(vec![message]);
+                Ok(None)
+            }
+            None if !begin && end => Err(io::Error::new(io::ErrorKind::InvalidData, "Batch end frame without batch begin")),
+            None => Ok(Some(FrameBatch { messages: vec![message] })),
+            Some(messages) if begin => {
+                // the end frame of the incomplete batch was lost, the new batch is still valid
+                if !end {
+                    self.pending = Some(vec![message]);
+                }
+                Err(io::Error::new(io::ErrorKind::InvalidData,
+                                   format!("Batch begin frame inside batch of {} frames", messages.len())))
+            }
+            Some(mut messages) => {
+                messages.push(message);
+                if end {
+                    Ok(Some(FrameBatch { messages }))
+                } else {
+                    self.pending = Some(messages);
+                    Ok(None)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use frame::ControlMessage;
+    use SingleRequestMessage;
+    use sbe::{ControlMessageType, EventType, ExecuteCommandRequest};
+
+    fn request(request_id: u64) -> TransportMessage {
+        let request = ControlMessageType::RequestTopology.with(&::message::TopologyRequest {}).unwrap();
+        TransportMessage::request(request_id, request)
+    }
+
+    fn flags(message: &TransportMessage) -> (bool, bool) {
+        (message.frame_header().is_batch_begin(), message.frame_header().is_batch_end())
+    }
+
+    #[test]
+    fn test_write_and_read_batch() {
+        let batch = FrameBatch::new(vec![request(1), TransportMessage::control(ControlMessage::KeepAlive), request(2)]);
+        let mut written = vec![];
+        batch.write_to_vec(&mut written).unwrap();
+
+        let mut buffer = vec![];
+        batch.to_writer(&mut buffer).unwrap();
+        assert_eq!(written, buffer);
+        TransportMessage::control(ControlMessage::KeepAlive).to_writer(&mut buffer).unwrap();
+
+        let mut reader = &buffer[..];
+        let batch = FrameBatch::from_reader(&mut reader).unwrap();
+        let batch_flags: Vec<_> = batch.messages().iter().map(flags).collect();
+        assert_eq!(vec![(true, false), (false, false), (false, true)], batch_flags);
+
+        match batch.messages()[2] {
+            TransportMessage::RequestResponse(ref response) => assert_eq!(2, response.request_header.request_id),
+            ref other => panic!("Unexpected message {:?}", other),
+        }
+
+        // frames outside of a batch are a unit of their own
+        let single = FrameBatch::from_reader(&mut reader).unwrap();
+        assert_eq!(1, single.len());
+        assert_eq!((false, false), flags(&single.messages()[0]));
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_single_message_batch() {
+        let batch = FrameBatch::new(vec![request(1)]);
+        assert_eq!((true, true), flags(&batch.messages()[0]));
+
+        let mut buffer = vec![];
+        batch.write_to_vec(&mut buffer).unwrap();
+        assert_eq!(1, FrameBatch::from_reader(&mut &buffer[..]).unwrap().len());
+    }
+
+    #[test]
+    fn test_end_without_begin() {
+        let mut message = request(1);
+        message.frame_header_mut().set_batch_end(true);
+
+        let error = BatchAssembler::new().push(message).unwrap_err();
+        assert_eq!("Batch end frame without batch begin", error.to_string());
+    }
+
+    #[test]
+    fn test_nested_begin() {
+        let mut messages = FrameBatch::new(vec![request(1), request(2), request(3)]).into_messages();
+        let nested = FrameBatch::new(vec![request(4), request(5)]).into_messages();
+
+        let mut assembler = BatchAssembler::new();
+        assert!(assembler.push(messages.remove(0)).unwrap().is_none());
+        assert!(assembler.push(messages.remove(0)).unwrap().is_none());
+        assert_eq!(2, assembler.pending());
+
+        let mut nested = nested.into_iter();
+        let error = assembler.push(nested.next().unwrap()).unwrap_err();
+        assert_eq!("Batch begin frame inside batch of 2 frames", error.to_string());
+        assert_eq!(1, assembler.pending());
+
+        // only the incomplete batch was discarded, the nested batch is completed by its end frame
+        let batch = assembler.push(nested.next().unwrap()).unwrap().unwrap();
+        let request_ids: Vec<_> = batch
+            .messages()
+            .iter()
+            .map(|message| match *message {
+                TransportMessage::RequestResponse(ref request) => request.request_header.request_id,
+                ref other => panic!("Unexpected message {:?}", other),
+            })
+            .collect();
+        assert_eq!(vec![4, 5], request_ids);
+        assert_eq!(0, assembler.pending());
+
+        // the end frame of the discarded batch has no begin anymore
+        assert!(assembler.push(messages.remove(0)).is_err());
+    }
+
+    #[test]
+    fn test_execute_command_batch() {
+        let command = |key| {
+            let request = ExecuteCommandRequest {
+                partition_id: 0,
+                position: 0,
+                key,
+                event_type: ::sbe::EventType::TaskEvent,
+                topic_name: "default-topic".into(),
+                command: vec![0xc0].into(),
+            };
+            TransportMessage::request(key, request)
+        };
+
+        let batch = FrameBatch::new((1..4).map(command));
+        let mut buffer = vec![];
+        batch.write_to_vec(&mut buffer).unwrap();
+        assert_eq!(batch.messages().iter().map(TransportMessage::length).sum::<usize>(), buffer.len());
+
+        let mut assembler = BatchAssembler::new();
+        let mut reader = &buffer[..];
+        let mut batches = vec![];
+        while !reader.is_empty() {
+            if let Some(batch) = assembler.push(TransportMessage::from_reader(&mut reader).unwrap()).unwrap() {
+                batches.push(batch);
+            }
+        }
+        assert_eq!(1, batches.len());
+        assert_eq!(3, batches[0].len());
+    }
+
+    #[test]
+    fn test_subscribed_event_batch() {
+        let dump: &[u8] = include_bytes!("../tests/dumps/task-subscription-locked-task.bin");
+        let event = || TransportMessage::from_reader(&mut &dump[..]).unwrap();
+
+        let batch = FrameBatch::new(vec![event(), request(1), event()]);
+        let mut buffer = vec![];
+        batch.to_writer(&mut buffer).unwrap();
+        assert_eq!(2 * dump.len() + request(1).length(), buffer.len());
+
+        let mut vectored = VectoredWriter::new();
+        batch.to_writer_vectored(&mut vectored).unwrap();
+        let mut written = vec![];
+        vectored.write_to(&mut written).unwrap();
+        assert_eq!(buffer, written);
+
+        let batch = FrameBatch::from_reader(&mut &buffer[..]).unwrap();
+        let batch_flags: Vec<_> = batch.messages().iter().map(flags).collect();
+        assert_eq!(vec![(true, false), (false, false), (false, true)], batch_flags);
+        match batch.messages()[2] {
+            TransportMessage::SingleRequest(ref request) => match *request.message() {
+                SingleRequestMessage::SubscribedEvent(ref event) => assert_eq!(EventType::TaskEvent, event.event_type),
+                ref other => panic!("Unexpected message {:?}", other),
+            },
+            ref other => panic!("Unexpected message {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use alloc::vec::Vec;
+use core::cmp::min;
+use frame::{DEFAULT_MAX_FRAME_LENGTH, DataFrameHeader};
+use io::{Error, FromReader, HasBlockLength, Read, Write};
+
+pub const DEFAULT_MAX_RETAINED: usize = 1024 * 1024;
+
+/// Default maximum length of a frame which is skipped, longer frames are left to close the connection.
+pub const DEFAULT_MAX_SKIP_LENGTH: usize = 16 * 1024 * 1024;
+
+const SKIP_CHUNK_LENGTH: usize = 4096;
+
+/// Handling of a frame which exceeds the maximum frame length, both fail with `FrameTooLarge`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameRecovery {
+    /// Don't read the frame, the connection has to be closed.
+    Close,
+    /// Discard the frame in chunks, so the next frame of the connection can be read. Frames longer
+    /// than the maximum skip length are not read like with `Close`.
+    Skip,
+}
+
+/// Scratch buffer which is reused for every frame read from or written to a connection.
+///
+/// The buffer grows to the largest frame seen, but shrinks back to `max_retained` bytes
+/// after a frame exceeding it was processed, so a single large message does not pin
+/// its memory for the lifetime of the connection. Frames longer than `max_frame_length` are
+/// rejected before the buffer grows.
+#[derive(Debug)]
+pub struct FrameBuffer {
+    buffer: Vec<u8>,
+    max_retained: usize,
+    max_frame_length: usize,
+    max_skip_length: usize,
+    recovery: FrameRecovery,
+}
+
+impl Default for FrameBuffer {
+    fn default() -> Self {
+        FrameBuffer::with_max_retained(DEFAULT_MAX_RETAINED)
+    }
+}
+
+impl FrameBuffer {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_max_retained(max_retained: usize) -> Self {
+        FrameBuffer {
+            buffer: Vec::new(),
+            max_retained,
+            max_frame_length: DEFAULT_MAX_FRAME_LENGTH,
+            max_skip_length: DEFAULT_MAX_SKIP_LENGTH,
+            recovery: FrameRecovery::Close,
+        }
+    }
+
+    pub fn max_retained(&self) -> usize {
+        self.max_retained
+    }
+
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_length
+    }
+
+    pub fn set_max_frame_length(&mut self, max_frame_length: usize) {
+        self.max_frame_length = max_frame_length;
+    }
+
+    pub fn max_skip_length(&self) -> usize {
+        self.max_skip_length
+    }
+
+    pub fn set_max_skip_length(&mut self, max_skip_length: usize) {
+        self.max_skip_length = max_skip_length;
+    }
+
+    pub fn recovery(&self) -> FrameRecovery {
+        self.recovery
+    }
+
+    pub fn set_recovery(&mut self, recovery: FrameRecovery) {
+        self.recovery = recovery;
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    /// Reads the next complete frame, including padding, and returns it.
+    pub fn read_frame<R: Read + ?Sized>(&mut self, reader: &mut R) -> Result<&[u8], Error> {
+        self.release();
+
+        let header_length = DataFrameHeader::block_length() as usize;
+        self.buffer.resize(header_length, 0);
+        reader.read_exact(&mut self.buffer)?;
+
+        let frame_header = DataFrameHeader::from_reader(&mut &self.buffer[..])?;
+        if let Err(mut error) = frame_header.check_length(self.max_frame_length) {
+            if self.recovery == FrameRecovery::Skip && frame_header.length() <= self.max_skip_length {
+                let length = frame_header.checked_aligned_length()?;
+                self.skip(reader, length - header_length)?;
+                error.skipped = true;
+            }
+            return Err(error.into());
+        }
+        let length = frame_header.checked_aligned_length()?;
+
+        self.buffer.resize(length, 0);
+        reader.read_exact(&mut self.buffer[header_length..])?;
+
+        Ok(&self.buffer)
+    }
+
+    /// Returns an empty buffer to encode the next frame into.
+    pub fn write_buffer(&mut self) -> &mut Vec<u8> {
+        self.release();
+        &mut self.buffer
+    }
+
+    pub fn write_to<W: Write + ?Sized>(&mut self, writer: &mut W) -> Result<(), Error> {
+        writer.write_all(&self.buffer)
+    }
+
+    fn skip<R: Read + ?Sized>(&mut self, reader: &mut R, mut remaining: usize) -> Result<(), Error> {
+        self.buffer.resize(min(remaining, SKIP_CHUNK_LENGTH), 0);
+        while remaining > 0 {
+            let chunk = min(remaining, self.buffer.len());
+            reader.read_exact(&mut self.buffer[..chunk])?;
+            remaining -= chunk;
+        }
+
+        Ok(())
+    }
+
+    fn release(&mut self) {
+        self.buffer.clear();
+        if self.buffer.capacity() > self.max_retained {
+            self.buffer.shrink_to(self.max_retained);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(not(feature = "std"))]
+    use alloc::string::ToString;
+    use frame::{DataFrameType, align};
+    use io::ToWriter;
+
+    fn frame(length: u32) -> Vec<u8> {
+        let header = DataFrameHeader {
+            length,
+            frame_type: DataFrameType::Message,
+            ..Default::default()
+        };
+
+        let mut buffer = vec![];
+        header.to_writer(&mut buffer).unwrap();
+        buffer.resize(header.aligned_length(), 1);
+        buffer
+    }
+
+    #[test]
+    fn read_frames() {
+        let mut frames = frame(3);
+        frames.extend(frame(20));
+
+        let mut reader = &frames[..];
+        let mut buffer = FrameBuffer::new();
+
+        assert_eq!(&frame(3)[..], buffer.read_frame(&mut reader).unwrap());
+        assert_eq!(&frame(20)[..], buffer.read_frame(&mut reader).unwrap());
+        assert!(buffer.read_frame(&mut reader).is_err());
+    }
+
+    #[test]
+    fn reuse_buffer() {
+        let frame = frame(100);
+        let mut buffer = FrameBuffer::new();
+
+        buffer.read_frame(&mut &frame[..]).unwrap();
+        let capacity = buffer.capacity();
+
+        buffer.read_frame(&mut &frame[..]).unwrap();
+        assert_eq!(capacity, buffer.capacity());
+    }
+
+    #[test]
+    fn shrink_to_max_retained() {
+        let max_retained = align(64);
+        let mut buffer = FrameBuffer::with_max_retained(max_retained);
+
+        buffer.read_frame(&mut &frame(1000)[..]).unwrap();
+        assert!(buffer.capacity() > max_retained);
+
+        buffer.read_frame(&mut &frame(10)[..]).unwrap();
+        assert!(buffer.capacity() <= max_retained);
+
+        buffer.write_buffer().extend_from_slice(&[1, 2, 3]);
+        let mut written = vec![];
+        buffer.write_to(&mut written).unwrap();
+        assert_eq!(vec![1, 2, 3], written);
+    }
+
+    #[test]
+    fn reject_frame_too_large() {
+        let mut frames = frame(10_000);
+        frames.extend(frame(10));
+
+        let mut reader = &frames[..];
+        let mut buffer = FrameBuffer::new();
+        buffer.set_max_frame_length(1000);
+
+        let error = buffer.read_frame(&mut reader).unwrap_err();
+        assert_eq!("Frame length 10000 exceeds maximum frame length 1000", error.to_string());
+        assert_eq!(frames.len() - DataFrameHeader::block_length() as usize, reader.len());
+        assert!(buffer.capacity() < 1000);
+    }
+
+    #[test]
+    fn skip_frame_too_large() {
+        let mut frames = frame(10_000);
+        frames.extend(frame(10));
+
+        let mut reader = &frames[..];
+        let mut buffer = FrameBuffer::new();
+        buffer.set_max_frame_length(1000);
+        buffer.set_recovery(FrameRecovery::Skip);
+
+        let error = buffer.read_frame(&mut reader).unwrap_err();
+        assert_eq!("Frame length 10000 exceeds maximum frame length 1000, frame was skipped", error.to_string());
+        assert!(buffer.capacity() <= SKIP_CHUNK_LENGTH);
+
+        assert_eq!(&frame(10)[..], buffer.read_frame(&mut reader).unwrap());
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn close_frame_exceeding_max_skip_length() {
+        let mut frames = frame(10_000);
+        frames.extend(frame(10));
+
+        let mut reader = &frames[..];
+        let mut buffer = FrameBuffer::new();
+        buffer.set_max_frame_length(1000);
+        buffer.set_max_skip_length(5000);
+        buffer.set_recovery(FrameRecovery::Skip);
+
+        let error = buffer.read_frame(&mut reader).unwrap_err();
+        assert_eq!("Frame length 10000 exceeds maximum frame length 1000", error.to_string());
+        assert_eq!(frames.len() - DataFrameHeader::block_length() as usize, reader.len());
+    }
+}
@@ -1,6 +1,8 @@
-use io::{FromBytes, HasBlockLength, ToBytes};
+use core::fmt;
+use io::{Error, ErrorKind, HasBlockLength};
+use schema::Schema;
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength)]
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, Describe)]
 #[enum_type = "u16"]
 pub enum DataFrameType {
     Message,
@@ -13,11 +15,21 @@ impl Default for DataFrameType {
     }
 }
 
-#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, SbeFlags)]
+pub struct FrameFlags {
+    #[sbe(bit = "7")]
+    pub batch_begin: bool,
+    #[sbe(bit = "6")]
+    pub batch_end: bool,
+    #[sbe(bit = "5")]
+    pub failed: bool,
+}
+
+#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, Describe)]
 pub struct DataFrameHeader {
     pub length: u32,
     pub version: u8,
-    pub flags: u8,
+    pub flags: FrameFlags,
     pub frame_type: DataFrameType,
     pub stream_id: u32,
 }
@@ -31,24 +43,98 @@ impl DataFrameHeader {
         align(self.length() + Self::block_length() as usize)
     }
 
+    /// Aligned length of a frame read from the connection, its length may overflow `usize` on
+    /// 32-bit targets, so call `check_length` before.
+    pub fn checked_aligned_length(&self) -> Result<usize, Error> {
+        self.length()
+            .checked_add(Self::block_length() as usize + 7)
+            .map(|length| length & !7)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("Frame length {} overflows", self.length())))
+    }
+
     pub fn padding(&self) -> usize {
         self.aligned_length() - self.length() - Self::block_length() as usize
     }
 
     pub fn is_batch_begin(&self) -> bool {
-        self.flags & 0b1000_0000 != 0
+        self.flags.batch_begin
+    }
+
+    pub fn set_batch_begin(&mut self, batch_begin: bool) {
+        self.flags.batch_begin = batch_begin;
     }
 
     pub fn is_batch_end(&self) -> bool {
-        self.flags & 0b0100_0000 != 0
+        self.flags.batch_end
+    }
+
+    pub fn set_batch_end(&mut self, batch_end: bool) {
+        self.flags.batch_end = batch_end;
     }
 
     pub fn is_failed(&self) -> bool {
-        self.flags & 0b0010_0000 != 0
+        self.flags.failed
+    }
+
+    pub fn set_failed(&mut self, failed: bool) {
+        self.flags.failed = failed;
+    }
+
+    /// Checks the length before a buffer for the frame is allocated, as the header may be corrupted.
+    pub fn check_length(&self, max_length: usize) -> Result<(), FrameTooLarge> {
+        if self.length() > max_length {
+            return Err(FrameTooLarge {
+                length: self.length(),
+                max_length,
+                skipped: false,
+            });
+        }
+
+        Ok(())
     }
 }
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength)]
+/// Default maximum length of a frame's message, matches the broker's maximum message size.
+pub const DEFAULT_MAX_FRAME_LENGTH: usize = 512 * 1024;
+
+/// Frame exceeding the maximum frame length, it's returned wrapped in an `io::Error` of kind
+/// `InvalidData`. If the frame wasn't `skipped` the connection is left within the frame and has
+/// to be closed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameTooLarge {
+    pub length: usize,
+    pub max_length: usize,
+    pub skipped: bool,
+}
+
+impl FrameTooLarge {
+    #[cfg(feature = "std")]
+    pub fn from_error(error: &Error) -> Option<&FrameTooLarge> {
+        error.get_ref().and_then(|error| error.downcast_ref())
+    }
+}
+
+impl fmt::Display for FrameTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Frame length {} exceeds maximum frame length {}", self.length, self.max_length)?;
+        if self.skipped {
+            write!(f, ", frame was skipped")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for FrameTooLarge {}
+
+impl From<FrameTooLarge> for Error {
+    fn from(error: FrameTooLarge) -> Self {
+        Error::new(ErrorKind::InvalidData, error)
+    }
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, Describe)]
 #[enum_type = "u16"]
 pub enum TransportProtocol {
     RequestResponse,
@@ -56,31 +142,46 @@ pub enum TransportProtocol {
     ControlMessage,
 }
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength)]
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, Describe)]
 pub struct TransportHeader {
     pub protocol: TransportProtocol,
 }
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength)]
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, Describe)]
 pub struct RequestResponseHeader {
     pub request_id: u64,
 }
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength)]
+#[derive(Debug, Clone, Copy, PartialEq, FromBytes, ToBytes, HasBlockLength, Describe)]
 #[enum_type = "u32"]
 pub enum ControlMessage {
     KeepAlive,
 }
 
+pub fn transport_schema() -> Schema {
+    // the frame type is declared before the flags as in the upstream schema
+    Schema::new("io.zeebe.transport", 0, 1)
+        .semantic_version("0.1.0")
+        .description("Zeebe Transport Framing")
+        .with_type::<DataFrameType>()
+        .with_type::<FrameFlags>()
+        .with_type::<DataFrameHeader>()
+        .with_type::<TransportHeader>()
+        .with_type::<RequestResponseHeader>()
+        .with_type::<ControlMessage>()
+}
+
+pub const PADDING: &[u8] = &[0; 7];
+
 pub fn align(value: usize) -> usize {
     (value + 7) & !7
 }
 
-
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod test {
     use super::*;
     use byteorder::{LittleEndian, WriteBytesExt};
+    use io::{FromBytes, ToBytes};
 
     #[test]
     fn test_align() {
@@ -105,7 +206,7 @@ mod test {
         let header = DataFrameHeader {
             length: 10,
             version: 11,
-            flags: 224,
+            flags: FrameFlags::from_bits(224).unwrap(),
             frame_type: DataFrameType::Padding,
             stream_id: 13,
         };
@@ -127,6 +228,29 @@ mod test {
         assert!(header.is_failed());
     }
 
+    #[test]
+    fn test_frame_flags() {
+        let mut header = DataFrameHeader::default();
+        assert_eq!(0, header.flags.bits());
+
+        header.set_batch_begin(true);
+        assert_eq!(0b1000_0000, header.flags.bits());
+
+        header.set_batch_begin(false);
+        header.set_batch_end(true);
+        header.set_failed(true);
+        assert_eq!(0b0110_0000, header.flags.bits());
+        assert!(!header.is_batch_begin());
+        assert!(header.is_batch_end());
+        assert!(header.is_failed());
+
+        let mut bytes = vec![];
+        header.to_bytes(&mut bytes).unwrap();
+        bytes[5] |= 0b0000_0001;
+        let error = DataFrameHeader::from_bytes(&mut &bytes[..]).unwrap_err();
+        assert_eq!("Unknown bits 0x1 for FrameFlags", error.to_string());
+    }
+
     #[test]
     fn test_transport_header() {
         let mut buffer = vec![];
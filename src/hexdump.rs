@@ -0,0 +1,292 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::min;
+use core::fmt::Write;
+use core::ops::Range;
+use frame::{ControlMessage, DataFrameHeader, DataFrameType, RequestResponseHeader, TransportHeader, TransportProtocol};
+use io::{Error, ErrorKind, FromReader, HasBlockLength};
+use sbe::{self, MessageHeader};
+use schema::{CompositeDescriptor, Describe, Schema, TypeDescriptor};
+
+const BYTES_PER_LINE: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub range: Range<usize>,
+    pub label: String,
+}
+
+/// Labels the byte ranges of all frames in the buffer, bytes which can't be decoded are labeled as
+/// `undecoded` with the reason.
+pub fn annotate(buffer: &[u8]) -> Vec<Annotation> {
+    let schemas = match schemas() {
+        Ok(schemas) => schemas,
+        Err(e) => {
+            return vec![Annotation {
+                            range: 0..buffer.len(),
+                            label: format!("undecoded: {}", e),
+                        }]
+        }
+    };
+
+    let mut walker = Walker {
+        buffer,
+        schemas: &schemas,
+        position: 0,
+        annotations: Vec::new(),
+    };
+
+    while walker.position < buffer.len() {
+        if let Err(e) = walker.frame() {
+            let label = format!("undecoded: {}", e);
+            walker.annotations.push(Annotation {
+                range: walker.position..buffer.len(),
+                label,
+            });
+            break;
+        }
+    }
+
+    walker.annotations
+}
+
+/// Renders an annotated hexdump of all frames in the buffer, see `annotate`.
+pub fn render(buffer: &[u8]) -> String {
+    let mut dump = String::new();
+
+    for annotation in annotate(buffer) {
+        let bytes = &buffer[annotation.range.clone()];
+
+        let mut lines: Vec<_> = bytes.chunks(BYTES_PER_LINE).collect();
+        if lines.is_empty() {
+            lines.push(&[]);
+        }
+
+        for (idx, line) in lines.into_iter().enumerate() {
+            let hex: Vec<_> = line.iter().map(|byte| format!("{:02x}", byte)).collect();
+            let offset = annotation.range.start + idx * BYTES_PER_LINE;
+            if idx == 0 {
+                let _ = writeln!(dump, "{:08x}  {:width$}  {}", offset, hex.join(" "), annotation.label, width = BYTES_PER_LINE * 3 - 1);
+            } else {
+                let _ = writeln!(dump, "{:08x}  {}", offset, hex.join(" "));
+            }
+        }
+    }
+
+    dump
+}
+
+fn schemas() -> Result<Vec<Schema>, Error> {
+    Ok(vec![sbe::client_schema()?, sbe::raft_schema()?, sbe::gossip_schema()?])
+}
+
+struct Walker<'a> {
+    buffer: &'a [u8],
+    schemas: &'a [Schema],
+    position: usize,
+    annotations: Vec<Annotation>,
+}
+
+impl<'a> Walker<'a> {
+    fn frame(&mut self) -> Result<(), Error> {
+        let start = self.position;
+        let frame_header: DataFrameHeader = self.read()?;
+        self.fixed::<DataFrameHeader>()?;
+
+        let end = start + frame_header.aligned_length();
+        let message_end = start + DataFrameHeader::block_length() as usize + frame_header.length();
+
+        if frame_header.frame_type == DataFrameType::Message {
+            let transport_header: TransportHeader = self.read()?;
+            self.fixed::<TransportHeader>()?;
+
+            match transport_header.protocol {
+                TransportProtocol::RequestResponse if frame_header.is_failed() => self.fixed::<RequestResponseHeader>()?,
+                _ if frame_header.is_failed() => {}
+                TransportProtocol::RequestResponse => {
+                    self.fixed::<RequestResponseHeader>()?;
+                    self.message()?;
+                }
+                TransportProtocol::FullDuplexSingleMessage => self.message()?,
+                TransportProtocol::ControlMessage => self.fixed::<ControlMessage>()?,
+            }
+
+            if self.position > message_end {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      format!("Message exceeds frame length {} by {} bytes", frame_header.length(), self.position - message_end)));
+            }
+            if self.position < message_end {
+                let label = if frame_header.is_failed() { "failure body" } else { "undecoded" };
+                self.label(message_end - self.position, label.to_string())?;
+            }
+        }
+
+        let padding = min(end, self.buffer.len()) - self.position;
+        self.label(padding, "padding".to_string())
+    }
+
+    fn message(&mut self) -> Result<(), Error> {
+        let message_header: MessageHeader = self.read()?;
+        self.fixed::<MessageHeader>()?;
+
+        let schemas = self.schemas;
+        let message = schemas
+            .iter()
+            .flat_map(|schema| &schema.messages)
+            .find(|message| message.template_id == message_header.template_id && message.schema_id == message_header.schema_id)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("Unsupported message {:?}", message_header)))?;
+
+        let composite = &message.composite;
+        self.composite(composite.name, composite, message_header.block_length as usize, message_header.version)
+    }
+
+    fn fixed<T: Describe>(&mut self) -> Result<(), Error> {
+        match T::describe() {
+            TypeDescriptor::Composite(composite) => self.composite(composite.name, &composite, composite.block_length(), u16::MAX),
+            other => {
+                let label = other.name().map(String::from).unwrap_or_else(|| format!("{:?}", other));
+                self.label(other.block_length().unwrap_or_default(), label)
+            }
+        }
+    }
+
+    // fields of older versions are missing, unknown fields of newer versions are skipped by the block length
+    fn composite(&mut self, prefix: &str, composite: &CompositeDescriptor, block_length: usize, version: u16) -> Result<(), Error> {
+        let present = |since_version: Option<u16>| since_version.unwrap_or(0) <= version;
+        let block_end = self.position + block_length;
+
+        for field in composite.fields.iter().filter(|field| field.offset.is_some() && present(field.since_version)) {
+            let width = field.width().unwrap_or_default();
+            if self.position + width > block_end {
+                break;
+            }
+            self.label(width, format!("{}.{}", prefix, field.name))?;
+        }
+
+        if self.position < block_end {
+            self.label(block_end - self.position, format!("{} (unknown fields)", prefix))?;
+        }
+
+        for field in composite.fields.iter().filter(|field| field.is_var_length() && present(field.since_version)) {
+            match field.ty {
+                TypeDescriptor::Group(ref entry) => {
+                    let entry = match **entry {
+                        TypeDescriptor::Composite(ref entry) => entry,
+                        ref other => return Err(Error::new(ErrorKind::InvalidData, format!("Unsupported group entry {:?}", other))),
+                    };
+
+                    let block_length: u16 = self.read()?;
+                    self.label(2, format!("{}.{}.block_length", prefix, field.name))?;
+                    let count: u8 = self.read()?;
+                    self.label(1, format!("{}.{}.num_in_group", prefix, field.name))?;
+
+                    for idx in 0..count {
+                        self.composite(&format!("{}.{}[{}]", prefix, field.name, idx), entry, block_length as usize, version)?;
+                    }
+                }
+                _ => {
+                    let length: u16 = self.read()?;
+                    self.label(2, format!("{}.{}.length", prefix, field.name))?;
+                    self.label(length as usize, format!("{}.{}", prefix, field.name))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read<T: FromReader>(&self) -> Result<T, Error> {
+        T::from_reader(&mut &self.buffer[self.position..])
+    }
+
+    fn label(&mut self, length: usize, label: String) -> Result<(), Error> {
+        if self.position + length > self.buffer.len() {
+            return Err(Error::new(ErrorKind::UnexpectedEof,
+                                  format!("{} requires {} bytes but only {} remain", label, length, self.buffer.len() - self.position)));
+        }
+
+        self.annotations.push(Annotation {
+            range: self.position..self.position + length,
+            label,
+        });
+        self.position += length;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use TransportMessage;
+    use io::Data;
+    use sbe::{EventType, ExecuteCommandRequest};
+
+    fn request() -> Vec<u8> {
+        let request = ExecuteCommandRequest {
+            partition_id: 1,
+            position: 2,
+            key: 3,
+            event_type: EventType::TaskEvent,
+            topic_name: "default-topic".into(),
+            command: Data::from(vec![1, 2, 3]),
+        };
+
+        let mut buffer = vec![];
+        TransportMessage::request(42, request).write_to_vec(&mut buffer).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn test_annotate() {
+        let buffer = request();
+        let annotations = annotate(&buffer);
+
+        let labels: Vec<_> = annotations.iter().map(|annotation| annotation.label.as_str()).collect();
+        assert_eq!(vec!["DataFrameHeader.length",
+                        "DataFrameHeader.version",
+                        "DataFrameHeader.flags",
+                        "DataFrameHeader.frame_type",
+                        "DataFrameHeader.stream_id",
+                        "TransportHeader.protocol",
+                        "RequestResponseHeader.request_id",
+                        "MessageHeader.block_length",
+                        "MessageHeader.template_id",
+                        "MessageHeader.schema_id",
+                        "MessageHeader.version",
+                        "ExecuteCommandRequest.partition_id",
+                        "ExecuteCommandRequest.position",
+                        "ExecuteCommandRequest.key",
+                        "ExecuteCommandRequest.event_type",
+                        "ExecuteCommandRequest.topic_name.length",
+                        "ExecuteCommandRequest.topic_name",
+                        "ExecuteCommandRequest.command.length",
+                        "ExecuteCommandRequest.command",
+                        "padding"],
+                   labels);
+
+        assert_eq!(0..4, annotations[0].range);
+        assert_eq!(b"default-topic", &buffer[annotations[16].range.clone()]);
+        assert_eq!(buffer.len(), annotations.last().unwrap().range.end);
+    }
+
+    #[test]
+    fn test_annotate_truncated() {
+        let buffer = request();
+        let annotations = annotate(&buffer[..55]);
+
+        let last = annotations.last().unwrap();
+        assert_eq!(51..55, last.range);
+        assert_eq!("undecoded: ExecuteCommandRequest.topic_name requires 13 bytes but only 4 remain", last.label);
+    }
+
+    #[test]
+    fn test_render() {
+        let dump = render(&request());
+        let lines: Vec<_> = dump.lines().collect();
+
+        assert_eq!("00000000  39 00 00 00                                      DataFrameHeader.length", lines[0]);
+        assert_eq!("00000033  64 65 66 61 75 6c 74 2d 74 6f 70 69 63           ExecuteCommandRequest.topic_name", lines[16]);
+        assert_eq!("00000045  00 00 00                                         padding", lines[19]);
+    }
+}
@@ -1,23 +1,175 @@
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use alloc::string::String;
+use alloc::vec::Vec;
+use byteorder::{ByteOrder, LittleEndian};
+use core::fmt;
+use core::mem::size_of;
+#[cfg(feature = "std")]
 use rmp_serde::{Deserializer, Serializer};
+#[cfg(feature = "std")]
 use rmp_serde::encode::StructMapWriter;
+#[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
-use std::fmt;
-use std::io::{self, Read, Write};
-use std::mem::size_of;
+
+#[cfg(not(feature = "std"))]
+pub use core_io::{Error, ErrorKind, Read, Write};
+#[cfg(feature = "std")]
+pub use std::io::{Error, ErrorKind, Read, Write};
+#[cfg(feature = "std")]
+use std::io::IoSlice;
 
 pub trait FromBytes {
-    fn from_bytes(reader: &mut Read) -> Result<Self, io::Error>
+    fn from_bytes(reader: &mut Read) -> Result<Self, Error>
+    where
+        Self: Sized;
+}
+
+pub trait FromBytesRef<'a> {
+    fn from_bytes_ref(buffer: &mut &'a [u8]) -> Result<Self, Error>
     where
         Self: Sized;
 }
 
 pub trait ToBytes {
-    fn to_bytes(&self, writer: &mut Write) -> Result<(), io::Error>;
+    fn to_bytes(&self, writer: &mut Write) -> Result<(), Error>;
+
+    fn to_bytes_vectored<'a>(&'a self, writer: &mut VectoredWriter<'a>) -> Result<(), Error> {
+        self.to_bytes(writer)
+    }
+}
+
+/// Statically dispatched variant of `FromBytes`, every `FromReader` is also `FromBytes`.
+pub trait FromReader: Sized {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, Error>;
+}
+
+/// Statically dispatched variant of `ToBytes`, every `ToWriter` is also `ToBytes`.
+pub trait ToWriter {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), Error>;
+
+    fn to_writer_vectored<'a>(&'a self, writer: &mut VectoredWriter<'a>) -> Result<(), Error> {
+        self.to_writer(writer)
+    }
+}
+
+/// Decodes a message as written by the given schema version, see `#[sbe(since_version)]`.
+pub trait FromReaderVersion: Sized {
+    fn from_reader_version<R: Read + ?Sized>(reader: &mut R, version: u16) -> Result<Self, Error>;
+
+    /// Decodes a message with the block length of its message header, so fixed fields of a newer
+    /// version are skipped. Derived implementations use the block length, by default it is ignored.
+    fn from_reader_block<R: Read + ?Sized>(reader: &mut R, block_length: u16, version: u16) -> Result<Self, Error> {
+        let _ = block_length;
+        Self::from_reader_version(reader, version)
+    }
+}
+
+/// Encodes a message for the given schema version, see `#[sbe(since_version)]`.
+pub trait ToWriterVersion {
+    fn to_writer_version<W: Write + ?Sized>(&self, writer: &mut W, version: u16) -> Result<(), Error>;
+
+    fn to_writer_vectored_version<'a>(&'a self, writer: &mut VectoredWriter<'a>, version: u16) -> Result<(), Error> {
+        self.to_writer_version(writer, version)
+    }
+}
+
+impl<T: FromReader> FromBytes for T {
+    fn from_bytes(reader: &mut Read) -> Result<Self, Error> {
+        T::from_reader(reader)
+    }
+}
+
+impl<T: ToWriter> ToBytes for T {
+    fn to_bytes(&self, writer: &mut Write) -> Result<(), Error> {
+        self.to_writer(writer)
+    }
+
+    fn to_bytes_vectored<'a>(&'a self, writer: &mut VectoredWriter<'a>) -> Result<(), Error> {
+        self.to_writer_vectored(writer)
+    }
+}
+
+// used by derived `FromReader` implementations of enums
+#[doc(hidden)]
+pub fn unknown_variant(value: u64, name: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("Unknown variant {} for {}", value, name))
+}
+
+// used by derived `FromReader` implementations of flags
+#[doc(hidden)]
+pub fn unknown_bits(bits: u64, name: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("Unknown bits {:#x} for {}", bits, name))
+}
+
+/// Limits the fixed fields of a message to its block length and skips the unknown remainder, used
+/// by derived `FromReaderVersion` implementations.
+#[doc(hidden)]
+pub struct BlockReader<'r, R: Read + ?Sized + 'r> {
+    reader: &'r mut R,
+    remaining: usize,
+}
+
+impl<'r, R: Read + ?Sized> BlockReader<'r, R> {
+    pub fn new(reader: &'r mut R, block_length: u16) -> Self {
+        BlockReader {
+            reader,
+            remaining: block_length as usize,
+        }
+    }
+
+    pub fn finish(self) -> Result<(), Error> {
+        let mut skip = [0; 64];
+        let mut remaining = self.remaining;
+        while remaining > 0 {
+            let length = remaining.min(skip.len());
+            self.reader.read_exact(&mut skip[..length])?;
+            remaining -= length;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'r, R: Read + ?Sized> Read for BlockReader<'r, R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        if !buf.is_empty() && self.remaining == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "Message fields exceed block length"));
+        }
+
+        let length = buf.len().min(self.remaining);
+        let read = self.reader.read(&mut buf[..length])?;
+        self.remaining -= read;
+
+        Ok(read)
+    }
+}
+
+// used by derived `FromBytesRef` implementations, splits the block of the fixed fields off the buffer
+#[doc(hidden)]
+pub fn split_block<'a>(buffer: &mut &'a [u8], block_length: u16) -> Result<&'a [u8], Error> {
+    let block_length = block_length as usize;
+    if buffer.len() < block_length {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            format!("Block length {} exceeds remaining {} bytes", block_length, buffer.len()),
+        ));
+    }
+
+    let (block, remaining) = buffer.split_at(block_length);
+    *buffer = remaining;
+    Ok(block)
 }
 
 pub trait HasBlockLength {
     fn block_length() -> u16;
+
+    fn block_length_version(_version: u16) -> u16 {
+        Self::block_length()
+    }
+}
+
+/// Block length usable in const contexts, derived along with `HasBlockLength`.
+pub trait ConstBlockLength: HasBlockLength {
+    const BLOCK_LENGTH: u16;
 }
 
 pub trait Message {
@@ -26,29 +178,42 @@ pub trait Message {
     fn version() -> u16;
 }
 
+/// Message ids usable in const contexts, derived along with `Message`.
+pub trait ConstMessage: Message {
+    const TEMPLATE_ID: u16;
+    const SCHEMA_ID: u16;
+    const VERSION: u16;
+}
+
 pub trait HasData {
     fn data(&self) -> &Data;
 }
 
+#[cfg(feature = "std")]
 pub trait FromData {
-    fn from_data<H: HasData>(has_data: &H) -> Result<Self, io::Error>
+    fn from_data<H: HasData>(has_data: &H) -> Result<Self, Error>
     where
         Self: Sized;
 }
 
+#[cfg(feature = "std")]
 pub trait ToData {
-    fn to_data(&self) -> Result<Data, io::Error>;
+    fn to_data(&self) -> Result<Data, Error>;
 }
 
 pub trait HasMessageLength {
     fn message_length(&self) -> u32;
+
+    fn message_length_version(&self, _version: u16) -> u32 {
+        self.message_length()
+    }
 }
 
 macro_rules! impl_has_message_length {
     ($t:ty) => (
         impl HasMessageLength for $t {
             fn message_length(&self) -> u32 {
-                ::std::mem::size_of::<$t>() as u32
+                size_of::<$t>() as u32
             }
         }
     )
@@ -63,151 +228,272 @@ impl_has_message_length!(i32);
 impl_has_message_length!(u64);
 impl_has_message_length!(i64);
 
-impl FromBytes for u8 {
-    fn from_bytes(reader: &mut Read) -> Result<Self, io::Error> {
-        reader.read_u8()
+/// Value which encodes an absent optional field, see `Option<T>`.
+pub trait NullValue {
+    fn null_value() -> Self;
+}
+
+macro_rules! impl_null_value {
+    ($t:ty, $null:expr) => (
+        impl NullValue for $t {
+            fn null_value() -> Self {
+                $null
+            }
+        }
+    )
+}
+
+impl_null_value!(u8, u8::MAX);
+impl_null_value!(i8, i8::MIN);
+impl_null_value!(u16, u16::MAX);
+impl_null_value!(i16, i16::MIN);
+impl_null_value!(u32, u32::MAX);
+impl_null_value!(i32, i32::MIN);
+impl_null_value!(u64, u64::MAX);
+impl_null_value!(i64, i64::MIN);
+
+impl<T: FromReader + NullValue + PartialEq> FromReader for Option<T> {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, Error> {
+        let value = T::from_reader(reader)?;
+        if value == T::null_value() {
+            Ok(None)
+        } else {
+            Ok(Some(value))
+        }
     }
 }
 
-impl ToBytes for u8 {
-    fn to_bytes(&self, writer: &mut Write) -> Result<(), io::Error> {
-        writer.write_u8(*self)
+impl<T: ToWriter + NullValue> ToWriter for Option<T> {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), Error> {
+        match *self {
+            Some(ref value) => value.to_writer(writer),
+            None => T::null_value().to_writer(writer),
+        }
+    }
+}
+
+impl<T: HasBlockLength> HasBlockLength for Option<T> {
+    fn block_length() -> u16 {
+        T::block_length()
+    }
+}
+
+impl<T: ConstBlockLength> ConstBlockLength for Option<T> {
+    const BLOCK_LENGTH: u16 = T::BLOCK_LENGTH;
+}
+
+impl<T: HasBlockLength> HasMessageLength for Option<T> {
+    fn message_length(&self) -> u32 {
+        u32::from(T::block_length())
+    }
+}
+
+impl FromReader for u8 {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, Error> {
+        let mut buffer = [0; 1];
+        reader.read_exact(&mut buffer)?;
+        Ok(buffer[0])
+    }
+}
+
+impl ToWriter for u8 {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), Error> {
+        writer.write_all(&[*self])
     }
 }
 
 impl HasBlockLength for u8 {
     fn block_length() -> u16 {
-        size_of::<u8>() as u16
+        Self::BLOCK_LENGTH
     }
 }
 
-impl FromBytes for i8 {
-    fn from_bytes(reader: &mut Read) -> Result<Self, io::Error> {
-        reader.read_i8()
+impl ConstBlockLength for u8 {
+    const BLOCK_LENGTH: u16 = size_of::<u8>() as u16;
+}
+
+impl FromReader for i8 {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, Error> {
+        let mut buffer = [0; 1];
+        reader.read_exact(&mut buffer)?;
+        Ok(buffer[0] as i8)
     }
 }
 
-impl ToBytes for i8 {
-    fn to_bytes(&self, writer: &mut Write) -> Result<(), io::Error> {
-        writer.write_i8(*self)
+impl ToWriter for i8 {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), Error> {
+        writer.write_all(&[*self as u8])
     }
 }
 
 impl HasBlockLength for i8 {
     fn block_length() -> u16 {
-        size_of::<i8>() as u16
+        Self::BLOCK_LENGTH
     }
 }
 
-impl FromBytes for u16 {
-    fn from_bytes(reader: &mut Read) -> Result<Self, io::Error> {
-        reader.read_u16::<LittleEndian>()
+impl ConstBlockLength for i8 {
+    const BLOCK_LENGTH: u16 = size_of::<i8>() as u16;
+}
+
+impl FromReader for u16 {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, Error> {
+        let mut buffer = [0; 2];
+        reader.read_exact(&mut buffer)?;
+        Ok(LittleEndian::read_u16(&buffer))
     }
 }
 
-impl ToBytes for u16 {
-    fn to_bytes(&self, writer: &mut Write) -> Result<(), io::Error> {
-        writer.write_u16::<LittleEndian>(*self)
+impl ToWriter for u16 {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), Error> {
+        let mut buffer = [0; 2];
+        LittleEndian::write_u16(&mut buffer, *self);
+        writer.write_all(&buffer)
     }
 }
 
 impl HasBlockLength for u16 {
     fn block_length() -> u16 {
-        size_of::<u16>() as u16
+        Self::BLOCK_LENGTH
     }
 }
 
-impl FromBytes for i16 {
-    fn from_bytes(reader: &mut Read) -> Result<Self, io::Error> {
-        reader.read_i16::<LittleEndian>()
+impl ConstBlockLength for u16 {
+    const BLOCK_LENGTH: u16 = size_of::<u16>() as u16;
+}
+
+impl FromReader for i16 {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, Error> {
+        let mut buffer = [0; 2];
+        reader.read_exact(&mut buffer)?;
+        Ok(LittleEndian::read_i16(&buffer))
     }
 }
 
-impl ToBytes for i16 {
-    fn to_bytes(&self, writer: &mut Write) -> Result<(), io::Error> {
-        writer.write_i16::<LittleEndian>(*self)
+impl ToWriter for i16 {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), Error> {
+        let mut buffer = [0; 2];
+        LittleEndian::write_i16(&mut buffer, *self);
+        writer.write_all(&buffer)
     }
 }
 
 impl HasBlockLength for i16 {
     fn block_length() -> u16 {
-        size_of::<i16>() as u16
+        Self::BLOCK_LENGTH
     }
 }
 
-impl FromBytes for u32 {
-    fn from_bytes(reader: &mut Read) -> Result<Self, io::Error> {
-        reader.read_u32::<LittleEndian>()
+impl ConstBlockLength for i16 {
+    const BLOCK_LENGTH: u16 = size_of::<i16>() as u16;
+}
+
+impl FromReader for u32 {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, Error> {
+        let mut buffer = [0; 4];
+        reader.read_exact(&mut buffer)?;
+        Ok(LittleEndian::read_u32(&buffer))
     }
 }
 
-impl ToBytes for u32 {
-    fn to_bytes(&self, writer: &mut Write) -> Result<(), io::Error> {
-        writer.write_u32::<LittleEndian>(*self)
+impl ToWriter for u32 {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), Error> {
+        let mut buffer = [0; 4];
+        LittleEndian::write_u32(&mut buffer, *self);
+        writer.write_all(&buffer)
     }
 }
 
 impl HasBlockLength for u32 {
     fn block_length() -> u16 {
-        size_of::<u32>() as u16
+        Self::BLOCK_LENGTH
     }
 }
 
-impl FromBytes for i32 {
-    fn from_bytes(reader: &mut Read) -> Result<Self, io::Error> {
-        reader.read_i32::<LittleEndian>()
+impl ConstBlockLength for u32 {
+    const BLOCK_LENGTH: u16 = size_of::<u32>() as u16;
+}
+
+impl FromReader for i32 {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, Error> {
+        let mut buffer = [0; 4];
+        reader.read_exact(&mut buffer)?;
+        Ok(LittleEndian::read_i32(&buffer))
     }
 }
 
-impl ToBytes for i32 {
-    fn to_bytes(&self, writer: &mut Write) -> Result<(), io::Error> {
-        writer.write_i32::<LittleEndian>(*self)
+impl ToWriter for i32 {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), Error> {
+        let mut buffer = [0; 4];
+        LittleEndian::write_i32(&mut buffer, *self);
+        writer.write_all(&buffer)
     }
 }
 
 impl HasBlockLength for i32 {
     fn block_length() -> u16 {
-        size_of::<i32>() as u16
+        Self::BLOCK_LENGTH
     }
 }
 
-impl FromBytes for u64 {
-    fn from_bytes(reader: &mut Read) -> Result<Self, io::Error> {
-        reader.read_u64::<LittleEndian>()
+impl ConstBlockLength for i32 {
+    const BLOCK_LENGTH: u16 = size_of::<i32>() as u16;
+}
+
+impl FromReader for u64 {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, Error> {
+        let mut buffer = [0; 8];
+        reader.read_exact(&mut buffer)?;
+        Ok(LittleEndian::read_u64(&buffer))
     }
 }
 
-impl ToBytes for u64 {
-    fn to_bytes(&self, writer: &mut Write) -> Result<(), io::Error> {
-        writer.write_u64::<LittleEndian>(*self)
+impl ToWriter for u64 {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), Error> {
+        let mut buffer = [0; 8];
+        LittleEndian::write_u64(&mut buffer, *self);
+        writer.write_all(&buffer)
     }
 }
 
 impl HasBlockLength for u64 {
     fn block_length() -> u16 {
-        size_of::<u64>() as u16
+        Self::BLOCK_LENGTH
     }
 }
 
-impl FromBytes for i64 {
-    fn from_bytes(reader: &mut Read) -> Result<Self, io::Error> {
-        reader.read_i64::<LittleEndian>()
+impl ConstBlockLength for u64 {
+    const BLOCK_LENGTH: u16 = size_of::<u64>() as u16;
+}
+
+impl FromReader for i64 {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, Error> {
+        let mut buffer = [0; 8];
+        reader.read_exact(&mut buffer)?;
+        Ok(LittleEndian::read_i64(&buffer))
     }
 }
 
-impl ToBytes for i64 {
-    fn to_bytes(&self, writer: &mut Write) -> Result<(), io::Error> {
-        writer.write_i64::<LittleEndian>(*self)
+impl ToWriter for i64 {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), Error> {
+        let mut buffer = [0; 8];
+        LittleEndian::write_i64(&mut buffer, *self);
+        writer.write_all(&buffer)
     }
 }
 
 impl HasBlockLength for i64 {
     fn block_length() -> u16 {
-        size_of::<i64>() as u16
+        Self::BLOCK_LENGTH
     }
 }
 
-#[derive(PartialEq, Default, Serialize, Deserialize)]
+impl ConstBlockLength for i64 {
+    const BLOCK_LENGTH: u16 = size_of::<i64>() as u16;
+}
+
+#[derive(PartialEq, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct Data(Vec<u8>);
 
 impl fmt::Debug for Data {
@@ -216,7 +502,7 @@ impl fmt::Debug for Data {
     }
 }
 
-impl ::std::ops::Deref for Data {
+impl ::core::ops::Deref for Data {
     type Target = Vec<u8>;
     fn deref(&self) -> &Vec<u8> {
         &self.0
@@ -235,22 +521,28 @@ impl From<Vec<u8>> for Data {
     }
 }
 
-impl FromBytes for Data {
-    fn from_bytes(reader: &mut Read) -> Result<Self, io::Error> {
-        let length = reader.read_u16::<LittleEndian>()?;
-        let mut buffer = Vec::with_capacity(length as usize);
-        let mut handle = reader.take(u64::from(length));
-        handle.read_to_end(&mut buffer)?;
+impl FromReader for Data {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, Error> {
+        let length = u16::from_reader(reader)?;
+        let mut buffer = vec![0; length as usize];
+        reader.read_exact(&mut buffer)?;
         Ok(Data(buffer))
     }
 }
 
-impl ToBytes for Data {
-    fn to_bytes(&self, writer: &mut Write) -> Result<(), io::Error> {
+impl ToWriter for Data {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), Error> {
         let length = self.0.len() as u16;
-        writer.write_u16::<LittleEndian>(length)?;
+        length.to_writer(writer)?;
         writer.write_all(&self.0)
     }
+
+    fn to_writer_vectored<'a>(&'a self, writer: &mut VectoredWriter<'a>) -> Result<(), Error> {
+        let length = self.0.len() as u16;
+        length.to_writer(writer)?;
+        writer.write_borrowed(&self.0);
+        Ok(())
+    }
 }
 
 impl HasData for Data {
@@ -259,26 +551,39 @@ impl HasData for Data {
     }
 }
 
+impl<'a> From<&'a [u8]> for Data {
+    fn from(slice: &'a [u8]) -> Self {
+        Data(slice.to_vec())
+    }
+}
+
 impl HasMessageLength for Data {
     fn message_length(&self) -> u32 {
         (size_of::<u16>() + self.0.len()) as u32
     }
 }
 
-impl FromBytes for String {
-    fn from_bytes(reader: &mut Read) -> Result<Self, io::Error> {
-        let buffer: Data = FromBytes::from_bytes(reader)?;
+impl FromReader for String {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, Error> {
+        let buffer = Data::from_reader(reader)?;
 
-        String::from_utf8(buffer.to_vec()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        String::from_utf8(buffer.to_vec()).map_err(|e| Error::new(ErrorKind::InvalidData, e))
     }
 }
 
-impl ToBytes for String {
-    fn to_bytes(&self, writer: &mut Write) -> Result<(), io::Error> {
+impl ToWriter for String {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), Error> {
         let length = self.len() as u16;
-        writer.write_u16::<LittleEndian>(length)?;
+        length.to_writer(writer)?;
         writer.write_all(self.as_bytes())
     }
+
+    fn to_writer_vectored<'a>(&'a self, writer: &mut VectoredWriter<'a>) -> Result<(), Error> {
+        let length = self.len() as u16;
+        length.to_writer(writer)?;
+        writer.write_borrowed(self.as_bytes());
+        Ok(())
+    }
 }
 
 impl HasMessageLength for String {
@@ -287,53 +592,98 @@ impl HasMessageLength for String {
     }
 }
 
-impl<T: FromBytes> FromBytes for Vec<T> {
-    fn from_bytes(reader: &mut Read) -> Result<Self, io::Error> {
-        let _block_length = reader.read_u16::<LittleEndian>()?;
-        let num_in_group = reader.read_u8()?;
+impl<'a, T: FromReader> FromBytesRef<'a> for T {
+    fn from_bytes_ref(buffer: &mut &'a [u8]) -> Result<Self, Error> {
+        T::from_reader(buffer)
+    }
+}
+
+impl<'a> FromBytesRef<'a> for &'a [u8] {
+    fn from_bytes_ref(buffer: &mut &'a [u8]) -> Result<Self, Error> {
+        let length = u16::from_reader(buffer)? as usize;
+        if buffer.len() < length {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                format!("Expected {} bytes but only {} remaining", length, buffer.len()),
+            ));
+        }
+
+        let (data, remaining) = buffer.split_at(length);
+        *buffer = remaining;
+        Ok(data)
+    }
+}
+
+impl<'a> FromBytesRef<'a> for &'a str {
+    fn from_bytes_ref(buffer: &mut &'a [u8]) -> Result<Self, Error> {
+        let data: &'a [u8] = FromBytesRef::from_bytes_ref(buffer)?;
+
+        ::core::str::from_utf8(data).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+}
+
+impl<T: FromReader> FromReader for Vec<T> {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, Error> {
+        let _block_length = u16::from_reader(reader)?;
+        let num_in_group = u8::from_reader(reader)?;
         let mut group: Vec<T> = Vec::with_capacity(num_in_group as usize);
         for _ in 0..num_in_group {
-            group.push(T::from_bytes(reader)?);
+            group.push(T::from_reader(reader)?);
         }
         Ok(group)
     }
 }
 
-impl<T: ToBytes + HasBlockLength> ToBytes for Vec<T> {
-    fn to_bytes(&self, writer: &mut Write) -> Result<(), io::Error> {
-        writer.write_u16::<LittleEndian>(T::block_length())?;
+impl<T: ToWriter + HasBlockLength> ToWriter for Vec<T> {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), Error> {
+        T::block_length().to_writer(writer)?;
 
         let length = self.len() as u8;
-        writer.write_u8(length)?;
+        length.to_writer(writer)?;
 
         for element in self {
-            element.to_bytes(writer)?;
+            element.to_writer(writer)?;
+        }
+
+        Ok(())
+    }
+
+    fn to_writer_vectored<'a>(&'a self, writer: &mut VectoredWriter<'a>) -> Result<(), Error> {
+        T::block_length().to_writer(writer)?;
+
+        let length = self.len() as u8;
+        length.to_writer(writer)?;
+
+        for element in self {
+            element.to_writer_vectored(writer)?;
         }
 
         Ok(())
     }
 }
 
+#[cfg(feature = "std")]
 impl<'d, T> FromData for T
 where
     T: Deserialize<'d>,
 {
-    fn from_data<H: HasData>(has_data: &H) -> Result<Self, io::Error> {
+    fn from_data<H: HasData>(has_data: &H) -> Result<Self, Error> {
         let reader: &[u8] = has_data.data();
         let mut de = Deserializer::new(reader);
 
-        Deserialize::deserialize(&mut de).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        Deserialize::deserialize(&mut de).map_err(|e| Error::new(ErrorKind::InvalidData, e))
     }
 }
 
+#[cfg(feature = "std")]
 impl<T> ToData for T
 where
     T: Serialize,
 {
-    fn to_data(&self) -> Result<Data, io::Error> {
+    fn to_data(&self) -> Result<Data, Error> {
         let mut buffer = Vec::new();
         self.serialize(&mut Serializer::with(&mut buffer, StructMapWriter))
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
 
         Ok(Data(buffer))
     }
@@ -346,12 +696,272 @@ impl<T: HasMessageLength> HasMessageLength for Vec<T> {
     }
 }
 
-#[cfg(test)]
+impl<T: FromReader + Default, const N: usize> FromReader for [T; N] {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, Error> {
+        let mut array: [T; N] = ::core::array::from_fn(|_| T::default());
+        for element in array.iter_mut() {
+            *element = T::from_reader(reader)?;
+        }
+        Ok(array)
+    }
+}
+
+impl<T: ToWriter, const N: usize> ToWriter for [T; N] {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), Error> {
+        for element in self {
+            element.to_writer(writer)?;
+        }
+        Ok(())
+    }
+
+    fn to_writer_vectored<'a>(&'a self, writer: &mut VectoredWriter<'a>) -> Result<(), Error> {
+        for element in self {
+            element.to_writer_vectored(writer)?;
+        }
+        Ok(())
+    }
+}
+
+// evaluated at compile time, so an array exceeding the u16 block length fails the build
+const fn array_block_length(element_length: u16, n: usize) -> u16 {
+    let length = element_length as usize * n;
+    assert!(length <= u16::MAX as usize, "block length of array exceeds u16");
+    length as u16
+}
+
+impl<T: ConstBlockLength, const N: usize> HasBlockLength for [T; N] {
+    fn block_length() -> u16 {
+        Self::BLOCK_LENGTH
+    }
+}
+
+impl<T: ConstBlockLength, const N: usize> ConstBlockLength for [T; N] {
+    const BLOCK_LENGTH: u16 = array_block_length(T::BLOCK_LENGTH, N);
+}
+
+impl<T: ConstBlockLength, const N: usize> HasMessageLength for [T; N] {
+    fn message_length(&self) -> u32 {
+        u32::from(Self::block_length())
+    }
+}
+
+/// Fixed-length SBE `char[N]`, shorter strings are padded with `\0` which is trimmed on access.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CharArray<const N: usize>([u8; N]);
+
+impl<const N: usize> CharArray<N> {
+    pub fn new(value: &str) -> Result<Self, Error> {
+        if value.len() > N {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("String '{}' exceeds char array length {}", value, N),
+            ));
+        }
+        if !value.is_ascii() {
+            return Err(Error::new(ErrorKind::InvalidInput, format!("String '{}' is not ASCII", value)));
+        }
+
+        let mut array = [0; N];
+        array[..value.len()].copy_from_slice(value.as_bytes());
+        Ok(CharArray(array))
+    }
+
+    pub fn as_str(&self) -> &str {
+        let length = self.0.iter().position(|&c| c == 0).unwrap_or(N);
+        // only ASCII is accepted by `new` and `from_reader`
+        ::core::str::from_utf8(&self.0[..length]).unwrap_or_default()
+    }
+
+    pub fn as_bytes(&self) -> &[u8; N] {
+        &self.0
+    }
+}
+
+impl<const N: usize> Default for CharArray<N> {
+    fn default() -> Self {
+        CharArray([0; N])
+    }
+}
+
+impl<const N: usize> fmt::Debug for CharArray<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Display for CharArray<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<'a, const N: usize> ::core::convert::TryFrom<&'a str> for CharArray<N> {
+    type Error = Error;
+
+    fn try_from(value: &'a str) -> Result<Self, Error> {
+        CharArray::new(value)
+    }
+}
+
+impl<const N: usize> FromReader for CharArray<N> {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, Error> {
+        let mut array = [0; N];
+        reader.read_exact(&mut array)?;
+
+        if !array.is_ascii() {
+            return Err(Error::new(ErrorKind::InvalidData, format!("Char array {:?} is not ASCII", &array[..])));
+        }
+
+        Ok(CharArray(array))
+    }
+}
+
+impl<const N: usize> ToWriter for CharArray<N> {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), Error> {
+        writer.write_all(&self.0)
+    }
+}
+
+impl<const N: usize> HasBlockLength for CharArray<N> {
+    fn block_length() -> u16 {
+        Self::BLOCK_LENGTH
+    }
+}
+
+impl<const N: usize> ConstBlockLength for CharArray<N> {
+    const BLOCK_LENGTH: u16 = array_block_length(1, N);
+}
+
+impl<const N: usize> HasMessageLength for CharArray<N> {
+    fn message_length(&self) -> u32 {
+        N as u32
+    }
+}
+
+/// Payloads shorter than this are copied into the scratch buffer instead of being
+/// written as a separate slice, as an extra `IoSlice` is more expensive than the copy.
+pub const MIN_BORROWED_LENGTH: usize = 256;
+
+#[derive(Debug)]
+enum Segment<'a> {
+    Buffered(usize, usize),
+    Borrowed(&'a [u8]),
+}
+
+/// Collects the encoded bytes of a message as a list of slices, borrowing large payloads
+/// instead of copying them, so they can be written with a single `write_vectored` call.
+#[derive(Debug, Default)]
+pub struct VectoredWriter<'a> {
+    buffer: Vec<u8>,
+    segments: Vec<Segment<'a>>,
+}
+
+impl<'a> VectoredWriter<'a> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        VectoredWriter {
+            buffer: Vec::with_capacity(capacity),
+            segments: Vec::new(),
+        }
+    }
+
+    pub fn write_borrowed(&mut self, data: &'a [u8]) {
+        if data.len() < MIN_BORROWED_LENGTH {
+            self.buffer(data);
+        } else {
+            self.segments.push(Segment::Borrowed(data));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.segments
+            .iter()
+            .map(|segment| match *segment {
+                Segment::Buffered(start, end) => end - start,
+                Segment::Borrowed(data) => data.len(),
+            })
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[cfg(feature = "std")]
+    pub fn io_slices(&self) -> Vec<IoSlice<'_>> {
+        self.segments
+            .iter()
+            .map(|segment| match *segment {
+                Segment::Buffered(start, end) => IoSlice::new(&self.buffer[start..end]),
+                Segment::Borrowed(data) => IoSlice::new(data),
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "std")]
+    pub fn write_to(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        let mut slices = self.io_slices();
+        let mut slices = &mut slices[..];
+
+        while !slices.is_empty() {
+            match writer.write_vectored(slices) {
+                Ok(0) => return Err(Error::new(ErrorKind::WriteZero, "failed to write whole message")),
+                Ok(written) => IoSlice::advance_slices(&mut slices, written),
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub fn write_to(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        for segment in &self.segments {
+            match *segment {
+                Segment::Buffered(start, end) => writer.write_all(&self.buffer[start..end])?,
+                Segment::Borrowed(data) => writer.write_all(data)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn buffer(&mut self, data: &[u8]) {
+        let start = self.buffer.len();
+        self.buffer.extend_from_slice(data);
+        let end = self.buffer.len();
+
+        if let Some(&mut Segment::Buffered(_, ref mut last)) = self.segments.last_mut() {
+            if *last == start {
+                *last = end;
+                return;
+            }
+        }
+
+        self.segments.push(Segment::Buffered(start, end));
+    }
+}
+
+impl<'a> Write for VectoredWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.buffer(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
 mod test {
 
     use super::*;
     use byteorder::{LittleEndian, WriteBytesExt};
-    use io::Write;
 
     #[test]
     fn from_bytes_u8() {
@@ -481,6 +1091,29 @@ mod test {
         assert_eq!(vec![0, 0, 0, 1, 0, 0, 0, 0], buffer);
     }
 
+    #[test]
+    fn from_reader_static_dispatch() {
+        let mut buffer: &[u8] = &[1, 0, 2, 0, 0, 0, 3, 0, 102, 111, 111];
+
+        assert_eq!(1u16, u16::from_reader(&mut buffer).unwrap());
+        assert_eq!(2u32, u32::from_reader(&mut buffer).unwrap());
+        assert_eq!("foo", String::from_reader(&mut buffer).unwrap());
+        assert!(u8::from_reader(&mut buffer).is_err());
+
+        let mut cursor = ::std::io::Cursor::new(vec![4, 0, 1, 2, 3, 4]);
+        assert_eq!(Data::from(vec![1, 2, 3, 4]), Data::from_reader(&mut cursor).unwrap());
+    }
+
+    #[test]
+    fn to_writer_static_dispatch() {
+        let mut buffer = vec![];
+        1u16.to_writer(&mut buffer).unwrap();
+        "foo".to_string().to_writer(&mut buffer).unwrap();
+        vec![2u8].to_writer(&mut buffer).unwrap();
+
+        assert_eq!(vec![1, 0, 3, 0, 102, 111, 111, 1, 0, 1, 2], buffer);
+    }
+
     #[test]
     fn from_bytes_string() {
         let expected = "foobar".to_string();
@@ -523,6 +1156,51 @@ mod test {
         assert_eq!(vec![4, 0, 1, 2, 3, 4], buffer);
     }
 
+    #[test]
+    fn from_bytes_ref_str() {
+        let buffer = [6, 0, 102, 111, 111, 98, 97, 114, 1];
+        let mut reader = &buffer[..];
+
+        assert_eq!("foobar", <&str>::from_bytes_ref(&mut reader).unwrap());
+        assert_eq!(&[1], reader);
+    }
+
+    #[test]
+    fn from_bytes_ref_data() {
+        let buffer = [4, 0, 1, 2, 3, 4];
+        let mut reader = &buffer[..];
+
+        assert_eq!(&[1, 2, 3, 4], <&[u8]>::from_bytes_ref(&mut reader).unwrap());
+        assert!(reader.is_empty());
+
+        let mut truncated: &[u8] = &[4, 0, 1, 2];
+        assert!(<&[u8]>::from_bytes_ref(&mut truncated).is_err());
+    }
+
+    #[test]
+    fn to_bytes_vectored_data() {
+        let small = Data::from(vec![1, 2, 3, 4]);
+        let large = Data::from(vec![5; MIN_BORROWED_LENGTH]);
+
+        let mut writer = VectoredWriter::new();
+        small.to_bytes_vectored(&mut writer).unwrap();
+        large.to_bytes_vectored(&mut writer).unwrap();
+        1u8.to_bytes_vectored(&mut writer).unwrap();
+
+        assert_eq!(3, writer.io_slices().len());
+        assert_eq!(6 + 2 + MIN_BORROWED_LENGTH + 1, writer.len());
+
+        let mut expected = vec![];
+        small.to_bytes(&mut expected).unwrap();
+        large.to_bytes(&mut expected).unwrap();
+        1u8.to_bytes(&mut expected).unwrap();
+
+        let mut buffer = vec![];
+        writer.write_to(&mut buffer).unwrap();
+
+        assert_eq!(expected, buffer);
+    }
+
     #[test]
     fn from_bytes_collection() {
         let expected = vec![1u32, 2u32, 3u32, 4u32];
@@ -609,4 +1287,55 @@ mod test {
         );
     }
 
+    #[test]
+    fn from_bytes_option() {
+        let mut buffer: &[u8] = &[1, 0, 0xff, 0xff];
+        assert_eq!(Some(1u16), FromBytes::from_bytes(&mut buffer).unwrap());
+        assert_eq!(None::<u16>, FromBytes::from_bytes(&mut buffer).unwrap());
+    }
+
+    #[test]
+    fn to_bytes_option() {
+        let mut buffer = vec![];
+        Some(1i32).to_bytes(&mut buffer).unwrap();
+        None::<i32>.to_bytes(&mut buffer).unwrap();
+        assert_eq!(vec![1, 0, 0, 0, 0, 0, 0, 0x80], buffer);
+        assert_eq!(4, Option::<i32>::block_length());
+        assert_eq!(4, None::<i32>.message_length());
+    }
+
+    #[test]
+    fn from_bytes_array() {
+        let mut buffer: &[u8] = &[1, 0, 2, 0, 3];
+        assert_eq!([1u16, 2], <[u16; 2]>::from_bytes(&mut buffer).unwrap());
+        assert!(<[u16; 2]>::from_bytes(&mut buffer).is_err());
+        assert_eq!(4, <[u16; 2]>::block_length());
+    }
+
+    #[test]
+    fn to_bytes_array() {
+        let mut buffer = vec![];
+        [1u16, 2].to_bytes(&mut buffer).unwrap();
+        assert_eq!(vec![1, 0, 2, 0], buffer);
+    }
+
+    #[test]
+    fn char_array() {
+        let value = CharArray::<6>::new("foo").unwrap();
+        assert_eq!("foo", value.as_str());
+        assert_eq!(6, CharArray::<6>::block_length());
+        assert!(CharArray::<2>::new("foo").is_err());
+        assert!(CharArray::<6>::new("föo").is_err());
+
+        let mut buffer = vec![];
+        value.to_bytes(&mut buffer).unwrap();
+        assert_eq!(vec![b'f', b'o', b'o', 0, 0, 0], buffer);
+        assert_eq!(value, CharArray::from_bytes(&mut &buffer[..]).unwrap());
+
+        let full: CharArray<3> = FromBytes::from_bytes(&mut &b"bar"[..]).unwrap();
+        assert_eq!("bar", full.to_string());
+
+        let invalid: &[u8] = &[0xc3, 0xb6];
+        assert!(CharArray::<2>::from_bytes(&mut &invalid[..]).is_err());
+    }
 }
@@ -0,0 +1,106 @@
+use ControlRequest;
+use frame::ControlMessage;
+use io;
+use std::cmp::min;
+use std::time::{Duration, Instant};
+
+/// Keep alive scheduler of a connection which doesn't perform any IO itself, the owner of the
+/// connection reports sent and received frames and polls the scheduler, i.e. on `deadline`.
+#[derive(Debug)]
+pub struct KeepAlive {
+    interval: Duration,
+    timeout: Duration,
+    last_sent: Instant,
+    last_received: Instant,
+}
+
+impl KeepAlive {
+    pub fn new(interval: Duration, timeout: Duration, now: Instant) -> Self {
+        KeepAlive {
+            interval,
+            timeout,
+            last_sent: now,
+            last_received: now,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn sent(&mut self, now: Instant) {
+        self.last_sent = now;
+    }
+
+    pub fn received(&mut self, now: Instant) {
+        self.last_received = now;
+    }
+
+    /// Next instant at which `poll` has to be called.
+    pub fn deadline(&self) -> Instant {
+        min(self.last_sent + self.interval, self.last_received + self.timeout)
+    }
+
+    /// Returns the keep alive frame to send if nothing was sent within the interval, fails with
+    /// `TimedOut` if the peer is considered dead as nothing was received within the timeout.
+    pub fn poll(&mut self, now: Instant) -> Result<Option<ControlRequest>, io::Error> {
+        let silence = now.saturating_duration_since(self.last_received);
+        if silence >= self.timeout {
+            return Err(io::Error::new(io::ErrorKind::TimedOut,
+                                      format!("Nothing received from peer for {:?}, timeout is {:?}", silence, self.timeout)));
+        }
+
+        if now.saturating_duration_since(self.last_sent) >= self.interval {
+            self.last_sent = now;
+            return Ok(Some(ControlRequest::new(ControlMessage::KeepAlive)));
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_keep_alive() {
+        let start = Instant::now();
+        let second = Duration::from_secs(1);
+        let mut keep_alive = KeepAlive::new(5 * second, 15 * second, start);
+
+        assert_eq!(start + 5 * second, keep_alive.deadline());
+        assert!(keep_alive.poll(start + 4 * second).unwrap().is_none());
+
+        // sending other frames defers the keep alive
+        keep_alive.sent(start + 4 * second);
+        assert!(keep_alive.poll(start + 5 * second).unwrap().is_none());
+        assert_eq!(start + 9 * second, keep_alive.deadline());
+
+        let request = keep_alive.poll(start + 9 * second).unwrap().unwrap();
+        assert_eq!(ControlMessage::KeepAlive, *request.message());
+        assert!(keep_alive.poll(start + 10 * second).unwrap().is_none());
+
+        keep_alive.received(start + 12 * second);
+        assert_eq!(start + 14 * second, keep_alive.deadline());
+        assert!(keep_alive.poll(start + 14 * second).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_dead_peer() {
+        let start = Instant::now();
+        let second = Duration::from_secs(1);
+        let mut keep_alive = KeepAlive::new(5 * second, 7 * second, start);
+
+        assert!(keep_alive.poll(start + 5 * second).unwrap().is_some());
+        assert_eq!(start + 7 * second, keep_alive.deadline());
+
+        let error = keep_alive.poll(start + 7 * second).unwrap_err();
+        assert_eq!(io::ErrorKind::TimedOut, error.kind());
+        assert_eq!("Nothing received from peer for 7s, timeout is 7s", error.to_string());
+    }
+}
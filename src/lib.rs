@@ -1,23 +1,54 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[macro_use]
+extern crate alloc;
 extern crate byteorder;
+#[cfg(feature = "std")]
+extern crate core;
+#[cfg(feature = "std")]
+extern crate rmp_serde;
+#[cfg(feature = "std")]
 extern crate serde;
+#[cfg(feature = "std")]
+extern crate serde_bytes;
+#[cfg(feature = "std")]
 #[macro_use]
 extern crate serde_derive;
-extern crate serde_bytes;
-extern crate rmp_serde;
 #[macro_use]
 extern crate unterflow_protocol_derive;
 
+// allows derived implementations to refer to `::unterflow_protocol` from within this crate
+extern crate self as unterflow_protocol;
+
+pub mod batch;
+pub mod buffer;
+#[cfg(not(feature = "std"))]
+mod core_io;
 pub mod frame;
+pub mod hexdump;
 pub mod io;
+#[cfg(feature = "std")]
+pub mod keep_alive;
+#[cfg(feature = "std")]
+pub mod log;
+#[cfg(feature = "std")]
 pub mod message;
 pub mod sbe;
+pub mod schema;
+#[cfg(feature = "std")]
+pub mod stream;
 
+use buffer::FrameBuffer;
 use frame::*;
 use io::*;
-use sbe::{AppendRequest, ControlMessageRequest, ControlMessageResponse, ExecuteCommandRequest, ExecuteCommandResponse, MessageHeader,
-          SubscribedEvent, ToMessageHeader};
+use sbe::{AppendRequest, AppendRequestRef, AppendResponse, AppendResponseRef, ConfigurationRequest, ConfigurationRequestRef, ConfigurationResponse,
+          ConfigurationResponseRef, ControlMessageRequest, ControlMessageRequestRef, ControlMessageResponse, ControlMessageResponseRef,
+          ExecuteCommandRequest, ExecuteCommandRequestRef, ExecuteCommandResponse, ExecuteCommandResponseRef, GossipEvent, GossipEventRef,
+          JoinRequest, JoinRequestRef, JoinResponse, JoinResponseRef, LeaveRequest, LeaveRequestRef, LeaveResponse, LeaveResponseRef, MessageHeader,
+          PollRequest, PollRequestRef, PollResponse, PollResponseRef, SubscribedEvent, SubscribedEventRef, ToMessageHeader, VoteRequest,
+          VoteRequestRef, VoteResponse, VoteResponseRef};
 
-use std::io::{Read, Write};
+use alloc::vec::Vec;
 
 #[derive(Debug)]
 pub enum RequestResponseMessage {
@@ -25,25 +56,69 @@ pub enum RequestResponseMessage {
     ControlMessageResponse(ControlMessageResponse),
     ExecuteCommandRequest(ExecuteCommandRequest),
     ExecuteCommandResponse(ExecuteCommandResponse),
+    JoinRequest(JoinRequest),
+    JoinResponse(JoinResponse),
+    LeaveRequest(LeaveRequest),
+    LeaveResponse(LeaveResponse),
+    ConfigurationRequest(ConfigurationRequest),
+    ConfigurationResponse(ConfigurationResponse),
+    PollRequest(PollRequest),
+    PollResponse(PollResponse),
+    VoteRequest(VoteRequest),
+    VoteResponse(VoteResponse),
+    GossipEvent(GossipEvent),
 }
 
 impl RequestResponseMessage {
-    pub fn read<R: Read>(message_header: &MessageHeader, reader: &mut R) -> Result<Self, std::io::Error> {
-        if message_header == &ControlMessageRequest::message_header() {
-            let message = ControlMessageRequest::from_bytes(reader)?;
+    pub fn read<R: Read + ?Sized>(message_header: &MessageHeader, reader: &mut R) -> Result<Self, io::Error> {
+        if message_header.is_message::<ControlMessageRequest>() {
+            let message = ControlMessageRequest::from_reader_block(reader, message_header.block_length, message_header.version)?;
             Ok(RequestResponseMessage::ControlMessageRequest(message))
-        } else if message_header == &ControlMessageResponse::message_header() {
-            let message = ControlMessageResponse::from_bytes(reader)?;
+        } else if message_header.is_message::<ControlMessageResponse>() {
+            let message = ControlMessageResponse::from_reader_block(reader, message_header.block_length, message_header.version)?;
             Ok(RequestResponseMessage::ControlMessageResponse(message))
-        } else if message_header == &ExecuteCommandRequest::message_header() {
-            let message = ExecuteCommandRequest::from_bytes(reader)?;
+        } else if message_header.is_message::<ExecuteCommandRequest>() {
+            let message = ExecuteCommandRequest::from_reader_block(reader, message_header.block_length, message_header.version)?;
             Ok(RequestResponseMessage::ExecuteCommandRequest(message))
-        } else if message_header == &ExecuteCommandResponse::message_header() {
-            let message = ExecuteCommandResponse::from_bytes(reader)?;
+        } else if message_header.is_message::<ExecuteCommandResponse>() {
+            let message = ExecuteCommandResponse::from_reader_block(reader, message_header.block_length, message_header.version)?;
             Ok(RequestResponseMessage::ExecuteCommandResponse(message))
+        } else if message_header.is_message::<JoinRequest>() {
+            let message = JoinRequest::from_reader_block(reader, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessage::JoinRequest(message))
+        } else if message_header.is_message::<JoinResponse>() {
+            let message = JoinResponse::from_reader_block(reader, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessage::JoinResponse(message))
+        } else if message_header.is_message::<LeaveRequest>() {
+            let message = LeaveRequest::from_reader_block(reader, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessage::LeaveRequest(message))
+        } else if message_header.is_message::<LeaveResponse>() {
+            let message = LeaveResponse::from_reader_block(reader, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessage::LeaveResponse(message))
+        } else if message_header.is_message::<ConfigurationRequest>() {
+            let message = ConfigurationRequest::from_reader_block(reader, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessage::ConfigurationRequest(message))
+        } else if message_header.is_message::<ConfigurationResponse>() {
+            let message = ConfigurationResponse::from_reader_block(reader, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessage::ConfigurationResponse(message))
+        } else if message_header.is_message::<PollRequest>() {
+            let message = PollRequest::from_reader_block(reader, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessage::PollRequest(message))
+        } else if message_header.is_message::<PollResponse>() {
+            let message = PollResponse::from_reader_block(reader, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessage::PollResponse(message))
+        } else if message_header.is_message::<VoteRequest>() {
+            let message = VoteRequest::from_reader_block(reader, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessage::VoteRequest(message))
+        } else if message_header.is_message::<VoteResponse>() {
+            let message = VoteResponse::from_reader_block(reader, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessage::VoteResponse(message))
+        } else if message_header.is_message::<GossipEvent>() {
+            let message = GossipEvent::from_reader_block(reader, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessage::GossipEvent(message))
         } else {
-            Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
                 format!(
                     "Unsupported request response message {:?}",
                     message_header
@@ -77,19 +152,155 @@ impl From<ExecuteCommandResponse> for RequestResponseMessage {
     }
 }
 
-impl ToBytes for RequestResponseMessage {
-    fn to_bytes(&self, writer: &mut Write) -> Result<(), std::io::Error> {
-        let message: &ToBytes = match *self {
-            RequestResponseMessage::ControlMessageRequest(ref m) => m,
-            RequestResponseMessage::ControlMessageResponse(ref m) => m,
-            RequestResponseMessage::ExecuteCommandRequest(ref m) => m,
-            RequestResponseMessage::ExecuteCommandResponse(ref m) => m,
-        };
+impl From<JoinRequest> for RequestResponseMessage {
+    fn from(message: JoinRequest) -> Self {
+        RequestResponseMessage::JoinRequest(message)
+    }
+}
+
+impl From<JoinResponse> for RequestResponseMessage {
+    fn from(message: JoinResponse) -> Self {
+        RequestResponseMessage::JoinResponse(message)
+    }
+}
+
+impl From<LeaveRequest> for RequestResponseMessage {
+    fn from(message: LeaveRequest) -> Self {
+        RequestResponseMessage::LeaveRequest(message)
+    }
+}
+
+impl From<LeaveResponse> for RequestResponseMessage {
+    fn from(message: LeaveResponse) -> Self {
+        RequestResponseMessage::LeaveResponse(message)
+    }
+}
+
+impl From<ConfigurationRequest> for RequestResponseMessage {
+    fn from(message: ConfigurationRequest) -> Self {
+        RequestResponseMessage::ConfigurationRequest(message)
+    }
+}
+
+impl From<ConfigurationResponse> for RequestResponseMessage {
+    fn from(message: ConfigurationResponse) -> Self {
+        RequestResponseMessage::ConfigurationResponse(message)
+    }
+}
+
+impl From<PollRequest> for RequestResponseMessage {
+    fn from(message: PollRequest) -> Self {
+        RequestResponseMessage::PollRequest(message)
+    }
+}
+
+impl From<PollResponse> for RequestResponseMessage {
+    fn from(message: PollResponse) -> Self {
+        RequestResponseMessage::PollResponse(message)
+    }
+}
+
+impl From<VoteRequest> for RequestResponseMessage {
+    fn from(message: VoteRequest) -> Self {
+        RequestResponseMessage::VoteRequest(message)
+    }
+}
+
+impl From<VoteResponse> for RequestResponseMessage {
+    fn from(message: VoteResponse) -> Self {
+        RequestResponseMessage::VoteResponse(message)
+    }
+}
+
+impl From<GossipEvent> for RequestResponseMessage {
+    fn from(message: GossipEvent) -> Self {
+        RequestResponseMessage::GossipEvent(message)
+    }
+}
+
+impl ToWriter for RequestResponseMessage {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), io::Error> {
+        match *self {
+            RequestResponseMessage::ControlMessageRequest(ref m) => m.to_writer(writer),
+            RequestResponseMessage::ControlMessageResponse(ref m) => m.to_writer(writer),
+            RequestResponseMessage::ExecuteCommandRequest(ref m) => m.to_writer(writer),
+            RequestResponseMessage::ExecuteCommandResponse(ref m) => m.to_writer(writer),
+            RequestResponseMessage::JoinRequest(ref m) => m.to_writer(writer),
+            RequestResponseMessage::JoinResponse(ref m) => m.to_writer(writer),
+            RequestResponseMessage::LeaveRequest(ref m) => m.to_writer(writer),
+            RequestResponseMessage::LeaveResponse(ref m) => m.to_writer(writer),
+            RequestResponseMessage::ConfigurationRequest(ref m) => m.to_writer(writer),
+            RequestResponseMessage::ConfigurationResponse(ref m) => m.to_writer(writer),
+            RequestResponseMessage::PollRequest(ref m) => m.to_writer(writer),
+            RequestResponseMessage::PollResponse(ref m) => m.to_writer(writer),
+            RequestResponseMessage::VoteRequest(ref m) => m.to_writer(writer),
+            RequestResponseMessage::VoteResponse(ref m) => m.to_writer(writer),
+            RequestResponseMessage::GossipEvent(ref m) => m.to_writer(writer),
+        }
+    }
 
-        message.to_bytes(writer)
+    fn to_writer_vectored<'a>(&'a self, writer: &mut VectoredWriter<'a>) -> Result<(), io::Error> {
+        match *self {
+            RequestResponseMessage::ControlMessageRequest(ref m) => m.to_writer_vectored(writer),
+            RequestResponseMessage::ControlMessageResponse(ref m) => m.to_writer_vectored(writer),
+            RequestResponseMessage::ExecuteCommandRequest(ref m) => m.to_writer_vectored(writer),
+            RequestResponseMessage::ExecuteCommandResponse(ref m) => m.to_writer_vectored(writer),
+            RequestResponseMessage::JoinRequest(ref m) => m.to_writer_vectored(writer),
+            RequestResponseMessage::JoinResponse(ref m) => m.to_writer_vectored(writer),
+            RequestResponseMessage::LeaveRequest(ref m) => m.to_writer_vectored(writer),
+            RequestResponseMessage::LeaveResponse(ref m) => m.to_writer_vectored(writer),
+            RequestResponseMessage::ConfigurationRequest(ref m) => m.to_writer_vectored(writer),
+            RequestResponseMessage::ConfigurationResponse(ref m) => m.to_writer_vectored(writer),
+            RequestResponseMessage::PollRequest(ref m) => m.to_writer_vectored(writer),
+            RequestResponseMessage::PollResponse(ref m) => m.to_writer_vectored(writer),
+            RequestResponseMessage::VoteRequest(ref m) => m.to_writer_vectored(writer),
+            RequestResponseMessage::VoteResponse(ref m) => m.to_writer_vectored(writer),
+            RequestResponseMessage::GossipEvent(ref m) => m.to_writer_vectored(writer),
+        }
     }
 }
 
+impl ToWriterVersion for RequestResponseMessage {
+    fn to_writer_version<W: Write + ?Sized>(&self, writer: &mut W, version: u16) -> Result<(), io::Error> {
+        match *self {
+            RequestResponseMessage::ControlMessageRequest(ref m) => m.to_writer_version(writer, version),
+            RequestResponseMessage::ControlMessageResponse(ref m) => m.to_writer_version(writer, version),
+            RequestResponseMessage::ExecuteCommandRequest(ref m) => m.to_writer_version(writer, version),
+            RequestResponseMessage::ExecuteCommandResponse(ref m) => m.to_writer_version(writer, version),
+            RequestResponseMessage::JoinRequest(ref m) => m.to_writer_version(writer, version),
+            RequestResponseMessage::JoinResponse(ref m) => m.to_writer_version(writer, version),
+            RequestResponseMessage::LeaveRequest(ref m) => m.to_writer_version(writer, version),
+            RequestResponseMessage::LeaveResponse(ref m) => m.to_writer_version(writer, version),
+            RequestResponseMessage::ConfigurationRequest(ref m) => m.to_writer_version(writer, version),
+            RequestResponseMessage::ConfigurationResponse(ref m) => m.to_writer_version(writer, version),
+            RequestResponseMessage::PollRequest(ref m) => m.to_writer_version(writer, version),
+            RequestResponseMessage::PollResponse(ref m) => m.to_writer_version(writer, version),
+            RequestResponseMessage::VoteRequest(ref m) => m.to_writer_version(writer, version),
+            RequestResponseMessage::VoteResponse(ref m) => m.to_writer_version(writer, version),
+            RequestResponseMessage::GossipEvent(ref m) => m.to_writer_version(writer, version),
+        }
+    }
+
+    fn to_writer_vectored_version<'a>(&'a self, writer: &mut VectoredWriter<'a>, version: u16) -> Result<(), io::Error> {
+        match *self {
+            RequestResponseMessage::ControlMessageRequest(ref m) => m.to_writer_vectored_version(writer, version),
+            RequestResponseMessage::ControlMessageResponse(ref m) => m.to_writer_vectored_version(writer, version),
+            RequestResponseMessage::ExecuteCommandRequest(ref m) => m.to_writer_vectored_version(writer, version),
+            RequestResponseMessage::ExecuteCommandResponse(ref m) => m.to_writer_vectored_version(writer, version),
+            RequestResponseMessage::JoinRequest(ref m) => m.to_writer_vectored_version(writer, version),
+            RequestResponseMessage::JoinResponse(ref m) => m.to_writer_vectored_version(writer, version),
+            RequestResponseMessage::LeaveRequest(ref m) => m.to_writer_vectored_version(writer, version),
+            RequestResponseMessage::LeaveResponse(ref m) => m.to_writer_vectored_version(writer, version),
+            RequestResponseMessage::ConfigurationRequest(ref m) => m.to_writer_vectored_version(writer, version),
+            RequestResponseMessage::ConfigurationResponse(ref m) => m.to_writer_vectored_version(writer, version),
+            RequestResponseMessage::PollRequest(ref m) => m.to_writer_vectored_version(writer, version),
+            RequestResponseMessage::PollResponse(ref m) => m.to_writer_vectored_version(writer, version),
+            RequestResponseMessage::VoteRequest(ref m) => m.to_writer_vectored_version(writer, version),
+            RequestResponseMessage::VoteResponse(ref m) => m.to_writer_vectored_version(writer, version),
+            RequestResponseMessage::GossipEvent(ref m) => m.to_writer_vectored_version(writer, version),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct RequestResponse {
@@ -101,9 +312,9 @@ pub struct RequestResponse {
 }
 
 impl RequestResponse {
-    pub fn read<R: Read>(frame_header: DataFrameHeader, transport_header: TransportHeader, reader: &mut R) -> Result<Self, std::io::Error> {
-        let request_header = RequestResponseHeader::from_bytes(reader)?;
-        let message_header = MessageHeader::from_bytes(reader)?;
+    pub fn read<R: Read + ?Sized>(frame_header: DataFrameHeader, transport_header: TransportHeader, reader: &mut R) -> Result<Self, io::Error> {
+        let request_header = RequestResponseHeader::from_reader(reader)?;
+        let message_header = MessageHeader::from_reader(reader)?;
         let message = RequestResponseMessage::read(&message_header, reader)?;
 
         Ok(RequestResponse {
@@ -118,42 +329,95 @@ impl RequestResponse {
     pub fn message(&self) -> &RequestResponseMessage {
         &self.message
     }
+
+    pub fn write_to_slice(&self, buffer: &mut [u8]) -> Result<usize, io::Error> {
+        write_frame_to_slice(self, &self.frame_header, buffer)
+    }
+
+    pub fn write_to_vec(&self, buffer: &mut Vec<u8>) -> Result<(), io::Error> {
+        write_frame_to_vec(self, &self.frame_header, buffer)
+    }
+
+    pub fn write_vectored(&self, writer: &mut dyn Write) -> Result<(), io::Error> {
+        write_frame_vectored(self, writer)
+    }
 }
 
-impl ToBytes for RequestResponse {
-    fn to_bytes(&self, writer: &mut Write) -> Result<(), std::io::Error> {
-        let mut buffer = vec![0u8; self.frame_header.aligned_length()];
+impl ToWriter for RequestResponse {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), io::Error> {
+        self.frame_header.to_writer(writer)?;
+        self.transport_header.to_writer(writer)?;
+        self.request_header.to_writer(writer)?;
+        self.message_header.to_writer(writer)?;
+        self.message.to_writer_version(writer, self.message_header.version)?;
+        writer.write_all(&PADDING[..self.frame_header.padding()])
+    }
 
-        {
-            let mut buffer = buffer.as_mut_slice();
-            self.frame_header.to_bytes(&mut buffer)?;
-            self.transport_header.to_bytes(&mut buffer)?;
-            self.request_header.to_bytes(&mut buffer)?;
-            self.message_header.to_bytes(&mut buffer)?;
-            self.message.to_bytes(&mut buffer)?;
-        }
+    fn to_writer_vectored<'a>(&'a self, writer: &mut VectoredWriter<'a>) -> Result<(), io::Error> {
+        self.frame_header.to_writer(writer)?;
+        self.transport_header.to_writer(writer)?;
+        self.request_header.to_writer(writer)?;
+        self.message_header.to_writer(writer)?;
+        self.message.to_writer_vectored_version(writer, self.message_header.version)?;
+        writer.write_all(&PADDING[..self.frame_header.padding()])
+    }
+}
 
-        writer.write_all(buffer.as_slice())
+// the frame is written into the slice in place, bytes the message doesn't cover are zeroed as the
+// buffer may be reused
+fn write_frame_to_slice<T: ToWriter>(frame: &T, frame_header: &DataFrameHeader, buffer: &mut [u8]) -> Result<usize, io::Error> {
+    let length = frame_header.aligned_length();
+    if buffer.len() < length {
+        return Err(io::Error::new(
+            io::ErrorKind::WriteZero,
+            format!("Frame requires {} bytes but buffer has only {}", length, buffer.len()),
+        ));
     }
+
+    let mut writer = &mut buffer[..length];
+    frame.to_writer(&mut writer)?;
+    for byte in writer.iter_mut() {
+        *byte = 0;
+    }
+
+    Ok(length)
+}
+
+fn write_frame_to_vec<T: ToWriter>(frame: &T, frame_header: &DataFrameHeader, buffer: &mut Vec<u8>) -> Result<(), io::Error> {
+    let start = buffer.len();
+    buffer.resize(start + frame_header.aligned_length(), 0);
+
+    write_frame_to_slice(frame, frame_header, &mut buffer[start..]).map(|_| ())
+}
+
+fn write_frame_vectored<T: ToWriter>(frame: &T, writer: &mut dyn Write) -> Result<(), io::Error> {
+    let mut vectored = VectoredWriter::with_capacity(MIN_BORROWED_LENGTH);
+    frame.to_writer_vectored(&mut vectored)?;
+
+    vectored.write_to(writer)
 }
 
 #[derive(Debug)]
 pub enum SingleRequestMessage {
     SubscribedEvent(SubscribedEvent),
     AppendRequest(AppendRequest),
+    AppendResponse(AppendResponse),
 }
 
 impl SingleRequestMessage {
-    pub fn read<R: Read>(message_header: &MessageHeader, reader: &mut R) -> Result<Self, std::io::Error> {
-        if message_header == &SubscribedEvent::message_header() {
-            let message = SubscribedEvent::from_bytes(reader)?;
+    pub fn read<R: Read + ?Sized>(message_header: &MessageHeader, reader: &mut R) -> Result<Self, io::Error> {
+        if message_header.is_message::<SubscribedEvent>() {
+            let message = SubscribedEvent::from_reader_block(reader, message_header.block_length, message_header.version)?;
             Ok(SingleRequestMessage::SubscribedEvent(message))
-        } else if message_header == &AppendRequest::message_header() {
-            let message = AppendRequest::from_bytes(reader)?;
+        } else if message_header.is_message::<AppendRequest>() {
+            let message = AppendRequest::from_reader_block(reader, message_header.block_length, message_header.version)?;
             Ok(SingleRequestMessage::AppendRequest(message))
+        } else if message_header.is_message::<AppendResponse>() {
+            let message = AppendResponse::from_reader_block(reader, message_header.block_length, message_header.version)?;
+            Ok(SingleRequestMessage::AppendResponse(message))
         } else {
-            Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
                 format!(
                     "Unsupported single request message {:?}",
                     message_header
@@ -163,6 +427,42 @@ impl SingleRequestMessage {
     }
 }
 
+impl ToWriter for SingleRequestMessage {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), io::Error> {
+        match *self {
+            SingleRequestMessage::SubscribedEvent(ref m) => m.to_writer(writer),
+            SingleRequestMessage::AppendRequest(ref m) => m.to_writer(writer),
+            SingleRequestMessage::AppendResponse(ref m) => m.to_writer(writer),
+        }
+    }
+
+    fn to_writer_vectored<'a>(&'a self, writer: &mut VectoredWriter<'a>) -> Result<(), io::Error> {
+        match *self {
+            SingleRequestMessage::SubscribedEvent(ref m) => m.to_writer_vectored(writer),
+            SingleRequestMessage::AppendRequest(ref m) => m.to_writer_vectored(writer),
+            SingleRequestMessage::AppendResponse(ref m) => m.to_writer_vectored(writer),
+        }
+    }
+}
+
+impl ToWriterVersion for SingleRequestMessage {
+    fn to_writer_version<W: Write + ?Sized>(&self, writer: &mut W, version: u16) -> Result<(), io::Error> {
+        match *self {
+            SingleRequestMessage::SubscribedEvent(ref m) => m.to_writer_version(writer, version),
+            SingleRequestMessage::AppendRequest(ref m) => m.to_writer_version(writer, version),
+            SingleRequestMessage::AppendResponse(ref m) => m.to_writer_version(writer, version),
+        }
+    }
+
+    fn to_writer_vectored_version<'a>(&'a self, writer: &mut VectoredWriter<'a>, version: u16) -> Result<(), io::Error> {
+        match *self {
+            SingleRequestMessage::SubscribedEvent(ref m) => m.to_writer_vectored_version(writer, version),
+            SingleRequestMessage::AppendRequest(ref m) => m.to_writer_vectored_version(writer, version),
+            SingleRequestMessage::AppendResponse(ref m) => m.to_writer_vectored_version(writer, version),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct SingleRequest {
     pub frame_header: DataFrameHeader,
@@ -172,8 +472,8 @@ pub struct SingleRequest {
 }
 
 impl SingleRequest {
-    pub fn read<R: Read>(frame_header: DataFrameHeader, transport_header: TransportHeader, reader: &mut R) -> Result<Self, std::io::Error> {
-        let message_header = MessageHeader::from_bytes(reader)?;
+    pub fn read<R: Read + ?Sized>(frame_header: DataFrameHeader, transport_header: TransportHeader, reader: &mut R) -> Result<Self, io::Error> {
+        let message_header = MessageHeader::from_reader(reader)?;
         let message = SingleRequestMessage::read(&message_header, reader)?;
 
         Ok(SingleRequest {
@@ -187,6 +487,36 @@ impl SingleRequest {
     pub fn message(&self) -> &SingleRequestMessage {
         &self.message
     }
+
+    pub fn write_to_slice(&self, buffer: &mut [u8]) -> Result<usize, io::Error> {
+        write_frame_to_slice(self, &self.frame_header, buffer)
+    }
+
+    pub fn write_to_vec(&self, buffer: &mut Vec<u8>) -> Result<(), io::Error> {
+        write_frame_to_vec(self, &self.frame_header, buffer)
+    }
+
+    pub fn write_vectored(&self, writer: &mut dyn Write) -> Result<(), io::Error> {
+        write_frame_vectored(self, writer)
+    }
+}
+
+impl ToWriter for SingleRequest {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), io::Error> {
+        self.frame_header.to_writer(writer)?;
+        self.transport_header.to_writer(writer)?;
+        self.message_header.to_writer(writer)?;
+        self.message.to_writer_version(writer, self.message_header.version)?;
+        writer.write_all(&PADDING[..self.frame_header.padding()])
+    }
+
+    fn to_writer_vectored<'a>(&'a self, writer: &mut VectoredWriter<'a>) -> Result<(), io::Error> {
+        self.frame_header.to_writer(writer)?;
+        self.transport_header.to_writer(writer)?;
+        self.message_header.to_writer(writer)?;
+        self.message.to_writer_vectored_version(writer, self.message_header.version)?;
+        writer.write_all(&PADDING[..self.frame_header.padding()])
+    }
 }
 
 #[derive(Debug)]
@@ -197,8 +527,24 @@ pub struct ControlRequest {
 }
 
 impl ControlRequest {
-    pub fn read<R: Read>(frame_header: DataFrameHeader, transport_header: TransportHeader, reader: &mut R) -> Result<Self, std::io::Error> {
-        let message = ControlMessage::from_bytes(reader)?;
+    pub fn new(message: ControlMessage) -> Self {
+        let length = u32::from(TransportHeader::block_length()) + u32::from(ControlMessage::block_length());
+
+        ControlRequest {
+            frame_header: DataFrameHeader {
+                length,
+                version: 0,
+                flags: FrameFlags::default(),
+                frame_type: DataFrameType::Message,
+                stream_id: 0,
+            },
+            transport_header: TransportHeader { protocol: TransportProtocol::ControlMessage },
+            message,
+        }
+    }
+
+    pub fn read<R: Read + ?Sized>(frame_header: DataFrameHeader, transport_header: TransportHeader, reader: &mut R) -> Result<Self, io::Error> {
+        let message = ControlMessage::from_reader(reader)?;
 
         Ok(ControlRequest {
             frame_header,
@@ -210,40 +556,182 @@ impl ControlRequest {
     pub fn message(&self) -> &ControlMessage {
         &self.message
     }
+
+    pub fn write_to_slice(&self, buffer: &mut [u8]) -> Result<usize, io::Error> {
+        write_frame_to_slice(self, &self.frame_header, buffer)
+    }
+
+    pub fn write_to_vec(&self, buffer: &mut Vec<u8>) -> Result<(), io::Error> {
+        write_frame_to_vec(self, &self.frame_header, buffer)
+    }
+
+    pub fn write_vectored(&self, writer: &mut dyn Write) -> Result<(), io::Error> {
+        write_frame_vectored(self, writer)
+    }
+}
+
+impl ToWriter for ControlRequest {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), io::Error> {
+        self.frame_header.to_writer(writer)?;
+        self.transport_header.to_writer(writer)?;
+        self.message.to_writer(writer)?;
+        writer.write_all(&PADDING[..self.frame_header.padding()])
+    }
+}
+
+/// Frame with the failed flag set, its body is not decoded as the sender couldn't process the
+/// request, the request id is only present for the request response protocol.
+#[derive(Debug)]
+pub struct TransportFailure {
+    pub frame_header: DataFrameHeader,
+    pub transport_header: TransportHeader,
+    pub request_id: Option<u64>,
+    pub body: Data,
+}
+
+impl TransportFailure {
+    pub fn new(request_id: u64, body: Data) -> Self {
+        let length = u32::from(TransportHeader::block_length()) + u32::from(RequestResponseHeader::block_length()) + body.len() as u32;
+        let mut frame_header = DataFrameHeader {
+            length,
+            version: 0,
+            flags: FrameFlags::default(),
+            frame_type: DataFrameType::Message,
+            stream_id: 0,
+        };
+        frame_header.set_failed(true);
+
+        TransportFailure {
+            frame_header,
+            transport_header: TransportHeader { protocol: TransportProtocol::RequestResponse },
+            request_id: Some(request_id),
+            body,
+        }
+    }
+
+    pub fn read<R: Read + ?Sized>(frame_header: DataFrameHeader, transport_header: TransportHeader, reader: &mut R) -> Result<Self, io::Error> {
+        let (request_id, body_length) = TransportFailure::read_request_id(&frame_header, &transport_header, reader)?;
+        let mut body = vec![0; body_length];
+        reader.read_exact(&mut body)?;
+
+        Ok(TransportFailure {
+            frame_header,
+            transport_header,
+            request_id,
+            body: body.into(),
+        })
+    }
+
+    pub fn write_to_slice(&self, buffer: &mut [u8]) -> Result<usize, io::Error> {
+        write_frame_to_slice(self, &self.frame_header, buffer)
+    }
+
+    pub fn write_to_vec(&self, buffer: &mut Vec<u8>) -> Result<(), io::Error> {
+        write_frame_to_vec(self, &self.frame_header, buffer)
+    }
+
+    pub fn write_vectored(&self, writer: &mut dyn Write) -> Result<(), io::Error> {
+        write_frame_vectored(self, writer)
+    }
+
+    // returns the request id and the length of the body which follows it
+    fn read_request_id<R: Read + ?Sized>(frame_header: &DataFrameHeader, transport_header: &TransportHeader, reader: &mut R) -> Result<(Option<u64>, usize), io::Error> {
+        let mut header_length = TransportHeader::block_length() as usize;
+        let request_id = match transport_header.protocol {
+            TransportProtocol::RequestResponse => {
+                header_length += RequestResponseHeader::block_length() as usize;
+                Some(RequestResponseHeader::from_reader(reader)?.request_id)
+            }
+            _ => None,
+        };
+
+        let body_length = frame_header.length().checked_sub(header_length).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Failed frame length {} is shorter than its headers", frame_header.length()),
+            )
+        })?;
+
+        Ok((request_id, body_length))
+    }
+
+    fn write_headers<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), io::Error> {
+        self.frame_header.to_writer(writer)?;
+        self.transport_header.to_writer(writer)?;
+        if let Some(request_id) = self.request_id {
+            RequestResponseHeader { request_id }.to_writer(writer)?;
+        }
+
+        Ok(())
+    }
 }
 
+impl ToWriter for TransportFailure {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), io::Error> {
+        self.write_headers(writer)?;
+        writer.write_all(&self.body)?;
+        writer.write_all(&PADDING[..self.frame_header.padding()])
+    }
+
+    fn to_writer_vectored<'a>(&'a self, writer: &mut VectoredWriter<'a>) -> Result<(), io::Error> {
+        self.write_headers(writer)?;
+        writer.write_borrowed(&self.body);
+        writer.write_all(&PADDING[..self.frame_header.padding()])
+    }
+}
 
 #[derive(Debug)]
 pub enum TransportMessage {
     RequestResponse(RequestResponse),
     SingleRequest(SingleRequest),
     ControlRequest(ControlRequest),
+    Failure(TransportFailure),
 }
 
 impl TransportMessage {
     pub fn request<M: Into<RequestResponseMessage> + ToMessageHeader + HasMessageLength>(request_id: u64, message: M) -> Self {
+        let version = M::message_header().version;
+        TransportMessage::request_version(request_id, version, message)
+    }
+
+    /// Creates a request encoded for the given schema version, i.e. to talk to an older broker.
+    pub fn request_version<M: Into<RequestResponseMessage> + ToMessageHeader + HasMessageLength>(request_id: u64, version: u16, message: M) -> Self {
         let length = u32::from(TransportHeader::block_length()) + u32::from(RequestResponseHeader::block_length()) +
-            u32::from(MessageHeader::block_length()) + message.message_length();
+            u32::from(MessageHeader::block_length()) + message.message_length_version(version);
 
         let request_response = RequestResponse {
             frame_header: DataFrameHeader {
                 length,
                 version: 0,
-                flags: 0,
+                flags: FrameFlags::default(),
                 frame_type: DataFrameType::Message,
                 stream_id: 0,
             },
             transport_header: TransportHeader { protocol: TransportProtocol::RequestResponse },
             request_header: RequestResponseHeader { request_id: request_id },
-            message_header: M::message_header(),
+            message_header: M::message_header_version(version),
             message: message.into(),
         };
 
         TransportMessage::RequestResponse(request_response)
     }
 
-    pub fn read<R: Read>(frame_header: DataFrameHeader, reader: &mut R) -> Result<Self, std::io::Error> {
-        let transport_header = TransportHeader::from_bytes(reader)?;
+    pub fn control(message: ControlMessage) -> Self {
+        TransportMessage::ControlRequest(ControlRequest::new(message))
+    }
+
+    pub fn failure(request_id: u64, body: Data) -> Self {
+        TransportMessage::Failure(TransportFailure::new(request_id, body))
+    }
+
+    /// Reads the message of the frame, frames with the failed flag set are returned as `Failure`.
+    pub fn read<R: Read + ?Sized>(frame_header: DataFrameHeader, reader: &mut R) -> Result<Self, io::Error> {
+        let transport_header = TransportHeader::from_reader(reader)?;
+        if frame_header.is_failed() {
+            let failure = TransportFailure::read(frame_header, transport_header, reader)?;
+            return Ok(TransportMessage::Failure(failure));
+        }
+
         match transport_header.protocol {
             TransportProtocol::RequestResponse => {
                 let message = RequestResponse::read(frame_header, transport_header, reader)?;
@@ -265,27 +753,78 @@ impl TransportMessage {
             TransportMessage::RequestResponse(ref r) => r.frame_header.aligned_length(),
             TransportMessage::ControlRequest(ref r) => r.frame_header.aligned_length(),
             TransportMessage::SingleRequest(ref r) => r.frame_header.aligned_length(),
+            TransportMessage::Failure(ref r) => r.frame_header.aligned_length(),
         }
     }
-}
 
-impl ToBytes for TransportMessage {
-    fn to_bytes(&self, writer: &mut Write) -> Result<(), std::io::Error> {
-        let message: &ToBytes = match *self {
-            TransportMessage::RequestResponse(ref m) => m,
-            _ => unimplemented!(),
-        };
+    pub fn frame_header(&self) -> &DataFrameHeader {
+        match *self {
+            TransportMessage::RequestResponse(ref r) => &r.frame_header,
+            TransportMessage::ControlRequest(ref r) => &r.frame_header,
+            TransportMessage::SingleRequest(ref r) => &r.frame_header,
+            TransportMessage::Failure(ref r) => &r.frame_header,
+        }
+    }
 
-        message.to_bytes(writer)
+    pub fn frame_header_mut(&mut self) -> &mut DataFrameHeader {
+        match *self {
+            TransportMessage::RequestResponse(ref mut r) => &mut r.frame_header,
+            TransportMessage::ControlRequest(ref mut r) => &mut r.frame_header,
+            TransportMessage::SingleRequest(ref mut r) => &mut r.frame_header,
+            TransportMessage::Failure(ref mut r) => &mut r.frame_header,
+        }
+    }
+
+    pub fn write_to_slice(&self, buffer: &mut [u8]) -> Result<usize, io::Error> {
+        match *self {
+            TransportMessage::RequestResponse(ref m) => m.write_to_slice(buffer),
+            TransportMessage::SingleRequest(ref m) => m.write_to_slice(buffer),
+            TransportMessage::ControlRequest(ref m) => m.write_to_slice(buffer),
+            TransportMessage::Failure(ref m) => m.write_to_slice(buffer),
+        }
     }
-}
 
-impl FromBytes for TransportMessage {
-    fn from_bytes(reader: &mut Read) -> Result<Self, std::io::Error> {
-        let frame_header = DataFrameHeader::from_bytes(reader)?;
+    pub fn write_to_vec(&self, buffer: &mut Vec<u8>) -> Result<(), io::Error> {
+        match *self {
+            TransportMessage::RequestResponse(ref m) => m.write_to_vec(buffer),
+            TransportMessage::SingleRequest(ref m) => m.write_to_vec(buffer),
+            TransportMessage::ControlRequest(ref m) => m.write_to_vec(buffer),
+            TransportMessage::Failure(ref m) => m.write_to_vec(buffer),
+        }
+    }
+
+    pub fn write_vectored(&self, writer: &mut dyn Write) -> Result<(), io::Error> {
+        match *self {
+            TransportMessage::RequestResponse(ref m) => m.write_vectored(writer),
+            TransportMessage::SingleRequest(ref m) => m.write_vectored(writer),
+            TransportMessage::ControlRequest(ref m) => m.write_vectored(writer),
+            TransportMessage::Failure(ref m) => m.write_vectored(writer),
+        }
+    }
+
+    pub fn from_reader_with<R: Read + ?Sized>(reader: &mut R, buffer: &mut FrameBuffer) -> Result<Self, io::Error> {
+        let mut frame = buffer.read_frame(reader)?;
+
+        let frame_header = DataFrameHeader::from_reader(&mut frame)?;
+        match frame_header.frame_type {
+            DataFrameType::Message => TransportMessage::read(frame_header, &mut frame),
+            _ => {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Expected message but received {:?}", frame_header),
+                ))
+            }
+        }
+    }
+
+    /// Reads the next frame, frames longer than `max_frame_length` are rejected before a buffer
+    /// is allocated.
+    pub fn from_reader_limited<R: Read + ?Sized>(reader: &mut R, max_frame_length: usize) -> Result<Self, io::Error> {
+        let frame_header = DataFrameHeader::from_reader(reader)?;
+        frame_header.check_length(max_frame_length)?;
         match frame_header.frame_type {
             DataFrameType::Message => {
-                let length = frame_header.aligned_length() - DataFrameHeader::block_length() as usize;
+                let length = frame_header.checked_aligned_length()? - DataFrameHeader::block_length() as usize;
                 let mut buffer = vec![0; length];
                 {
                     let mut buffer = buffer.as_mut_slice();
@@ -296,11 +835,318 @@ impl FromBytes for TransportMessage {
                 TransportMessage::read(frame_header, &mut buffer)
             }
             _ => {
-                Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Expected message but received {:?}", frame_header),
+                ))
+            }
+        }
+    }
+
+    pub fn to_writer_with<W: Write + ?Sized>(&self, writer: &mut W, buffer: &mut FrameBuffer) -> Result<(), io::Error> {
+        self.write_to_vec(buffer.write_buffer())?;
+        buffer.write_to(writer)
+    }
+}
+
+impl ToWriter for TransportMessage {
+    fn to_writer<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), io::Error> {
+        match *self {
+            TransportMessage::RequestResponse(ref m) => m.to_writer(writer),
+            TransportMessage::SingleRequest(ref m) => m.to_writer(writer),
+            TransportMessage::ControlRequest(ref m) => m.to_writer(writer),
+            TransportMessage::Failure(ref m) => m.to_writer(writer),
+        }
+    }
+
+    fn to_writer_vectored<'a>(&'a self, writer: &mut VectoredWriter<'a>) -> Result<(), io::Error> {
+        match *self {
+            TransportMessage::RequestResponse(ref m) => m.to_writer_vectored(writer),
+            TransportMessage::SingleRequest(ref m) => m.to_writer_vectored(writer),
+            TransportMessage::ControlRequest(ref m) => m.to_writer_vectored(writer),
+            TransportMessage::Failure(ref m) => m.to_writer_vectored(writer),
+        }
+    }
+}
+
+/// Rejects frames longer than `DEFAULT_MAX_FRAME_LENGTH`, use `from_reader_limited` or
+/// `from_reader_with` to configure the maximum frame length.
+impl FromReader for TransportMessage {
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, io::Error> {
+        TransportMessage::from_reader_limited(reader, DEFAULT_MAX_FRAME_LENGTH)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RequestResponseMessageRef<'a> {
+    ControlMessageRequest(ControlMessageRequestRef<'a>),
+    ControlMessageResponse(ControlMessageResponseRef<'a>),
+    ExecuteCommandRequest(ExecuteCommandRequestRef<'a>),
+    ExecuteCommandResponse(ExecuteCommandResponseRef<'a>),
+    JoinRequest(JoinRequestRef<'a>),
+    JoinResponse(JoinResponseRef),
+    LeaveRequest(LeaveRequestRef<'a>),
+    LeaveResponse(LeaveResponseRef),
+    ConfigurationRequest(ConfigurationRequestRef<'a>),
+    ConfigurationResponse(ConfigurationResponseRef),
+    PollRequest(PollRequestRef<'a>),
+    PollResponse(PollResponseRef),
+    VoteRequest(VoteRequestRef<'a>),
+    VoteResponse(VoteResponseRef),
+    GossipEvent(GossipEventRef<'a>),
+}
+
+impl<'a> RequestResponseMessageRef<'a> {
+    pub fn read(message_header: &MessageHeader, buffer: &mut &'a [u8]) -> Result<Self, io::Error> {
+        if message_header.is_message::<ControlMessageRequest>() {
+            let message = ControlMessageRequestRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessageRef::ControlMessageRequest(message))
+        } else if message_header.is_message::<ControlMessageResponse>() {
+            let message = ControlMessageResponseRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessageRef::ControlMessageResponse(message))
+        } else if message_header.is_message::<ExecuteCommandRequest>() {
+            let message = ExecuteCommandRequestRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessageRef::ExecuteCommandRequest(message))
+        } else if message_header.is_message::<ExecuteCommandResponse>() {
+            let message = ExecuteCommandResponseRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessageRef::ExecuteCommandResponse(message))
+        } else if message_header.is_message::<JoinRequest>() {
+            let message = JoinRequestRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessageRef::JoinRequest(message))
+        } else if message_header.is_message::<JoinResponse>() {
+            let message = JoinResponseRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessageRef::JoinResponse(message))
+        } else if message_header.is_message::<LeaveRequest>() {
+            let message = LeaveRequestRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessageRef::LeaveRequest(message))
+        } else if message_header.is_message::<LeaveResponse>() {
+            let message = LeaveResponseRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessageRef::LeaveResponse(message))
+        } else if message_header.is_message::<ConfigurationRequest>() {
+            let message = ConfigurationRequestRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessageRef::ConfigurationRequest(message))
+        } else if message_header.is_message::<ConfigurationResponse>() {
+            let message = ConfigurationResponseRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessageRef::ConfigurationResponse(message))
+        } else if message_header.is_message::<PollRequest>() {
+            let message = PollRequestRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessageRef::PollRequest(message))
+        } else if message_header.is_message::<PollResponse>() {
+            let message = PollResponseRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessageRef::PollResponse(message))
+        } else if message_header.is_message::<VoteRequest>() {
+            let message = VoteRequestRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessageRef::VoteRequest(message))
+        } else if message_header.is_message::<VoteResponse>() {
+            let message = VoteResponseRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessageRef::VoteResponse(message))
+        } else if message_header.is_message::<GossipEvent>() {
+            let message = GossipEventRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(RequestResponseMessageRef::GossipEvent(message))
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Unsupported request response message {:?}",
+                    message_header
+                ),
+            ))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RequestResponseRef<'a> {
+    pub frame_header: DataFrameHeader,
+    pub transport_header: TransportHeader,
+    pub request_header: RequestResponseHeader,
+    pub message_header: MessageHeader,
+    pub message: RequestResponseMessageRef<'a>,
+}
+
+impl<'a> RequestResponseRef<'a> {
+    pub fn read(frame_header: DataFrameHeader, transport_header: TransportHeader, buffer: &mut &'a [u8]) -> Result<Self, io::Error> {
+        let request_header = RequestResponseHeader::from_reader(buffer)?;
+        let message_header = MessageHeader::from_reader(buffer)?;
+        let message = RequestResponseMessageRef::read(&message_header, buffer)?;
+
+        Ok(RequestResponseRef {
+            frame_header,
+            transport_header,
+            request_header,
+            message_header,
+            message,
+        })
+    }
+
+    pub fn message(&self) -> &RequestResponseMessageRef<'a> {
+        &self.message
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SingleRequestMessageRef<'a> {
+    SubscribedEvent(SubscribedEventRef<'a>),
+    AppendRequest(AppendRequestRef<'a>),
+    AppendResponse(AppendResponseRef<'a>),
+}
+
+impl<'a> SingleRequestMessageRef<'a> {
+    pub fn read(message_header: &MessageHeader, buffer: &mut &'a [u8]) -> Result<Self, io::Error> {
+        if message_header.is_message::<SubscribedEvent>() {
+            let message = SubscribedEventRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(SingleRequestMessageRef::SubscribedEvent(message))
+        } else if message_header.is_message::<AppendRequest>() {
+            let message = AppendRequestRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(SingleRequestMessageRef::AppendRequest(message))
+        } else if message_header.is_message::<AppendResponse>() {
+            let message = AppendResponseRef::from_bytes_ref_block(buffer, message_header.block_length, message_header.version)?;
+            Ok(SingleRequestMessageRef::AppendResponse(message))
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Unsupported single request message {:?}",
+                    message_header
+                ),
+            ))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SingleRequestRef<'a> {
+    pub frame_header: DataFrameHeader,
+    pub transport_header: TransportHeader,
+    pub message_header: MessageHeader,
+    pub message: SingleRequestMessageRef<'a>,
+}
+
+impl<'a> SingleRequestRef<'a> {
+    pub fn read(frame_header: DataFrameHeader, transport_header: TransportHeader, buffer: &mut &'a [u8]) -> Result<Self, io::Error> {
+        let message_header = MessageHeader::from_reader(buffer)?;
+        let message = SingleRequestMessageRef::read(&message_header, buffer)?;
+
+        Ok(SingleRequestRef {
+            frame_header,
+            transport_header,
+            message_header,
+            message,
+        })
+    }
+
+    pub fn message(&self) -> &SingleRequestMessageRef<'a> {
+        &self.message
+    }
+}
+
+/// Borrowed variant of `TransportFailure`, the body refers to the buffer of the frame.
+#[derive(Debug)]
+pub struct TransportFailureRef<'a> {
+    pub frame_header: DataFrameHeader,
+    pub transport_header: TransportHeader,
+    pub request_id: Option<u64>,
+    pub body: &'a [u8],
+}
+
+impl<'a> TransportFailureRef<'a> {
+    pub fn read(frame_header: DataFrameHeader, transport_header: TransportHeader, buffer: &mut &'a [u8]) -> Result<Self, io::Error> {
+        let (request_id, body_length) = TransportFailure::read_request_id(&frame_header, &transport_header, buffer)?;
+        if buffer.len() < body_length {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("Expected body of {} bytes but only {} remaining", body_length, buffer.len()),
+            ));
+        }
+
+        let (body, remaining) = buffer.split_at(body_length);
+        *buffer = remaining;
+
+        Ok(TransportFailureRef {
+            frame_header,
+            transport_header,
+            request_id,
+            body,
+        })
+    }
+}
+
+/// Borrowed variant of `TransportMessage` which decodes a frame in place without allocating.
+#[derive(Debug)]
+pub enum TransportMessageRef<'a> {
+    RequestResponse(RequestResponseRef<'a>),
+    SingleRequest(SingleRequestRef<'a>),
+    ControlRequest(ControlRequest),
+    Failure(TransportFailureRef<'a>),
+}
+
+impl<'a> TransportMessageRef<'a> {
+    pub fn read(frame_header: DataFrameHeader, buffer: &mut &'a [u8]) -> Result<Self, io::Error> {
+        let transport_header = TransportHeader::from_reader(buffer)?;
+        if frame_header.is_failed() {
+            let failure = TransportFailureRef::read(frame_header, transport_header, buffer)?;
+            return Ok(TransportMessageRef::Failure(failure));
+        }
+
+        match transport_header.protocol {
+            TransportProtocol::RequestResponse => {
+                let message = RequestResponseRef::read(frame_header, transport_header, buffer)?;
+                Ok(TransportMessageRef::RequestResponse(message))
+            }
+            TransportProtocol::FullDuplexSingleMessage => {
+                let message = SingleRequestRef::read(frame_header, transport_header, buffer)?;
+                Ok(TransportMessageRef::SingleRequest(message))
+            }
+            TransportProtocol::ControlMessage => {
+                let message = ControlRequest::read(frame_header, transport_header, buffer)?;
+                Ok(TransportMessageRef::ControlRequest(message))
+            }
+        }
+    }
+
+    pub fn length(&self) -> usize {
+        match *self {
+            TransportMessageRef::RequestResponse(ref r) => r.frame_header.aligned_length(),
+            TransportMessageRef::ControlRequest(ref r) => r.frame_header.aligned_length(),
+            TransportMessageRef::SingleRequest(ref r) => r.frame_header.aligned_length(),
+            TransportMessageRef::Failure(ref r) => r.frame_header.aligned_length(),
+        }
+    }
+
+    pub fn from_bytes_ref_limited(buffer: &mut &'a [u8], max_frame_length: usize) -> Result<Self, io::Error> {
+        let frame_header = DataFrameHeader::from_reader(buffer)?;
+        frame_header.check_length(max_frame_length)?;
+        match frame_header.frame_type {
+            DataFrameType::Message => {
+                let length = frame_header.checked_aligned_length()? - DataFrameHeader::block_length() as usize;
+                if buffer.len() < length {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        format!("Expected frame of {} bytes but only {} remaining", length, buffer.len()),
+                    ));
+                }
+
+                let (frame, remaining) = buffer.split_at(length);
+                *buffer = remaining;
+
+                let mut frame = frame;
+                TransportMessageRef::read(frame_header, &mut frame)
+            }
+            _ => {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
                     format!("Expected message but received {:?}", frame_header),
                 ))
             }
         }
     }
 }
+
+/// Rejects frames longer than `DEFAULT_MAX_FRAME_LENGTH`, use `from_bytes_ref_limited` to
+/// configure the maximum frame length.
+impl<'a> FromBytesRef<'a> for TransportMessageRef<'a> {
+    fn from_bytes_ref(buffer: &mut &'a [u8]) -> Result<Self, io::Error> {
+        TransportMessageRef::from_bytes_ref_limited(buffer, DEFAULT_MAX_FRAME_LENGTH)
+    }
+}
+
@@ -0,0 +1,405 @@
+use frame::{DEFAULT_MAX_FRAME_LENGTH, DataFrameHeader, DataFrameType};
+use io::{self, ConstBlockLength, Data, FromData, FromReader, FromReaderVersion, Read};
+use message::{DeploymentEvent, RaftEvent, TaskEvent, TopicEvent, WorkInstanceEvent};
+use sbe::{BrokerEventMetadata, EventType, MessageHeader};
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{copy, sink, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Size of the logged event header between the data frame header and the metadata.
+pub const LOGGED_EVENT_HEADER_LENGTH: usize = 40;
+
+/// Size of the segment metadata (id, capacity and size) which precedes the frames of a segment file.
+pub const SEGMENT_METADATA_LENGTH: usize = 128;
+const SEGMENT_SIZE_OFFSET: usize = 64;
+
+#[derive(Debug, PartialEq)]
+pub struct LoggedEvent {
+    pub position: u64,
+    pub producer_id: i32,
+    pub source_event_partition_id: i32,
+    pub source_event_position: i64,
+    pub key: u64,
+    pub metadata: BrokerEventMetadata,
+    pub value: Data,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TypedEvent {
+    Task(TaskEvent),
+    Raft(RaftEvent),
+    Deployment(DeploymentEvent),
+    WorkflowInstance(WorkInstanceEvent),
+    Topic(TopicEvent),
+    Unknown(Data),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LogRecord {
+    pub position: u64,
+    pub key: u64,
+    pub event_type: EventType,
+    pub event: TypedEvent,
+}
+
+impl LoggedEvent {
+    /// Reads the logged event of a message frame, the frame header was already read. Frames longer
+    /// than `DEFAULT_MAX_FRAME_LENGTH` are rejected as the header may be corrupted.
+    pub fn read<R: Read + ?Sized>(frame_header: &DataFrameHeader, reader: &mut R) -> Result<Self, io::Error> {
+        frame_header.check_length(DEFAULT_MAX_FRAME_LENGTH)?;
+        if frame_header.length() < LOGGED_EVENT_HEADER_LENGTH {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      format!("Frame length {} is shorter than the logged event header", frame_header.length())));
+        }
+
+        let _version = u16::from_reader(reader)?;
+        let _reserved = u16::from_reader(reader)?;
+        let position = u64::from_reader(reader)?;
+        let producer_id = i32::from_reader(reader)?;
+        let source_event_partition_id = i32::from_reader(reader)?;
+        let source_event_position = i64::from_reader(reader)?;
+        let key = u64::from_reader(reader)?;
+        let _reserved = u16::from_reader(reader)?;
+        let metadata_length = u16::from_reader(reader)? as usize;
+
+        let value_length = frame_header
+            .length()
+            .checked_sub(LOGGED_EVENT_HEADER_LENGTH + metadata_length)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData,
+                               format!("Metadata length {} exceeds frame length {}", metadata_length, frame_header.length()))
+            })?;
+
+        let mut metadata_reader = Read::take(&mut *reader, metadata_length as u64);
+        let message_header = MessageHeader::from_reader(&mut metadata_reader)?;
+        if !message_header.is_message::<BrokerEventMetadata>() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      format!("Unsupported event metadata {:?}", message_header)));
+        }
+        let metadata = BrokerEventMetadata::from_reader_block(&mut metadata_reader, message_header.block_length, message_header.version)?;
+        // skip metadata fields of newer versions
+        copy(&mut metadata_reader, &mut sink())?;
+
+        // the value grows with the bytes actually read instead of trusting the frame length
+        let mut value = vec![];
+        Read::take(&mut *reader, value_length as u64).read_to_end(&mut value)?;
+        if value.len() < value_length {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                      format!("Expected value of {} bytes but only {} remaining", value_length, value.len())));
+        }
+
+        Ok(LoggedEvent {
+            position,
+            producer_id,
+            source_event_partition_id,
+            source_event_position,
+            key,
+            metadata,
+            value: value.into(),
+        })
+    }
+
+    pub fn event_type(&self) -> &EventType {
+        &self.metadata.event_type
+    }
+
+    pub fn into_record(self) -> Result<LogRecord, io::Error> {
+        let event = self.event()?;
+
+        Ok(LogRecord {
+            position: self.position,
+            key: self.key,
+            event_type: self.metadata.event_type,
+            event,
+        })
+    }
+
+    /// Decodes the msgpack value according to the event type of the metadata, values of event
+    /// types without a model are returned as `TypedEvent::Unknown`.
+    pub fn event(&self) -> Result<TypedEvent, io::Error> {
+        let event = match self.metadata.event_type {
+            EventType::TaskEvent => TypedEvent::Task(TaskEvent::from_data(&self.value)?),
+            EventType::RaftEvent => TypedEvent::Raft(RaftEvent::from_data(&self.value)?),
+            EventType::DeploymentEvent => TypedEvent::Deployment(DeploymentEvent::from_data(&self.value)?),
+            EventType::WorkflowInstanceEvent => TypedEvent::WorkflowInstance(WorkInstanceEvent::from_data(&self.value)?),
+            EventType::TopicEvent => TypedEvent::Topic(TopicEvent::from_data(&self.value)?),
+            _ => TypedEvent::Unknown(Data::from(&self.value[..])),
+        };
+
+        Ok(event)
+    }
+}
+
+// layout of the header: version (u16), reserved (u16), position (u64), producer id (i32), source
+// event partition id (i32), source event position (i64), key (u64), reserved (u16), metadata length (u16)
+impl FromReader for LoggedEvent {
+    /// Reads the next logged event, padding frames before it are skipped.
+    fn from_reader<R: Read + ?Sized>(reader: &mut R) -> Result<Self, io::Error> {
+        loop {
+            let frame_header = DataFrameHeader::from_reader(reader)?;
+            match frame_header.frame_type {
+                DataFrameType::Message => return LoggedEvent::read(&frame_header, reader),
+                DataFrameType::Padding => skip_frame(&frame_header, reader)?,
+            }
+        }
+    }
+}
+
+// skips the rest of a frame including its alignment padding
+fn skip_frame<R: Read + ?Sized>(frame_header: &DataFrameHeader, reader: &mut R) -> Result<(), io::Error> {
+    let length = (frame_header.checked_aligned_length()? - DataFrameHeader::BLOCK_LENGTH as usize) as u64;
+    copy(&mut Read::take(reader, length), &mut sink())?;
+    Ok(())
+}
+
+/// Iterator over the logged events of a reader, e.g. the `data` of an `AppendRequest`. Padding
+/// frames are skipped and the first error ends the iteration.
+pub struct LoggedEvents<R> {
+    reader: R,
+    done: bool,
+}
+
+pub fn logged_events(buffer: &[u8]) -> LoggedEvents<&[u8]> {
+    LoggedEvents::new(buffer)
+}
+
+impl<R: Read> LoggedEvents<R> {
+    pub fn new(reader: R) -> Self {
+        LoggedEvents { reader, done: false }
+    }
+
+    // distinguishes the end of the frames from a truncated frame header
+    fn next_frame_header(&mut self) -> Result<Option<DataFrameHeader>, io::Error> {
+        let mut buffer = [0; DataFrameHeader::BLOCK_LENGTH as usize];
+        let mut filled = 0;
+        while filled < buffer.len() {
+            match self.reader.read(&mut buffer[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        DataFrameHeader::from_reader(&mut &buffer[..filled]).map(Some)
+    }
+
+    fn next_event(&mut self) -> Result<Option<LoggedEvent>, io::Error> {
+        while let Some(frame_header) = self.next_frame_header()? {
+            match frame_header.frame_type {
+                DataFrameType::Message => {
+                    let length = (frame_header.checked_aligned_length()? - DataFrameHeader::BLOCK_LENGTH as usize) as u64;
+                    let mut frame = Read::take(&mut self.reader, length);
+                    let event = LoggedEvent::read(&frame_header, &mut frame)?;
+                    copy(&mut frame, &mut sink())?;
+                    return Ok(Some(event));
+                }
+                DataFrameType::Padding => {
+                    skip_frame(&frame_header, &mut self.reader)?;
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl<R: Read> Iterator for LoggedEvents<R> {
+    type Item = Result<LoggedEvent, io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.next_event() {
+            Ok(Some(event)) => Some(Ok(event)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Segment file of the broker's log storage, the frames of the segment start after the metadata
+/// and end at the segment size, the rest of the file is preallocated capacity.
+#[derive(Debug)]
+pub struct LogSegment {
+    pub id: u32,
+    pub capacity: u32,
+    size: usize,
+    source: SegmentSource,
+}
+
+#[derive(Debug)]
+enum SegmentSource {
+    File(PathBuf),
+    Buffer(Vec<u8>),
+}
+
+impl LogSegment {
+    /// Reads the metadata of the segment file, its frames are read buffered by `events` and never
+    /// the preallocated capacity.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        let file_length = file.metadata()?.len() as usize;
+        if file_length < SEGMENT_METADATA_LENGTH {
+            return Err(short_segment(file_length));
+        }
+
+        let mut metadata = [0; SEGMENT_METADATA_LENGTH];
+        BufReader::new(file).read_exact(&mut metadata)?;
+        let (id, capacity, size) = read_metadata(&metadata, file_length)?;
+
+        Ok(LogSegment {
+            id,
+            capacity,
+            size,
+            source: SegmentSource::File(path.to_path_buf()),
+        })
+    }
+
+    pub fn from_vec(mut buffer: Vec<u8>) -> Result<Self, io::Error> {
+        if buffer.len() < SEGMENT_METADATA_LENGTH {
+            return Err(short_segment(buffer.len()));
+        }
+
+        let (id, capacity, size) = read_metadata(&buffer, buffer.len())?;
+        buffer.truncate(size);
+
+        Ok(LogSegment {
+            id,
+            capacity,
+            size,
+            source: SegmentSource::Buffer(buffer),
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn events(&self) -> Result<LoggedEvents<Box<dyn Read + '_>>, io::Error> {
+        let reader: Box<dyn Read> = match self.source {
+            SegmentSource::File(ref path) => {
+                let mut file = File::open(path)?;
+                file.seek(SeekFrom::Start(SEGMENT_METADATA_LENGTH as u64))?;
+                let frames_length = (self.size - SEGMENT_METADATA_LENGTH) as u64;
+                Box::new(BufReader::new(file).take(frames_length))
+            }
+            SegmentSource::Buffer(ref buffer) => Box::new(&buffer[SEGMENT_METADATA_LENGTH..]),
+        };
+
+        Ok(LoggedEvents::new(reader))
+    }
+}
+
+fn short_segment(length: usize) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData,
+                   format!("Segment of {} bytes is shorter than its metadata", length))
+}
+
+// returns the id, capacity and size of the segment, which must fit into the segment's length
+fn read_metadata(metadata: &[u8], length: usize) -> Result<(u32, u32, usize), io::Error> {
+    let id = u32::from_reader(&mut &metadata[..])?;
+    let capacity = u32::from_reader(&mut &metadata[4..])?;
+    let size = u32::from_reader(&mut &metadata[SEGMENT_SIZE_OFFSET..])? as usize;
+
+    if size < SEGMENT_METADATA_LENGTH || size > length {
+        return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                  format!("Segment {} has invalid size {} for {} bytes", id, size, length)));
+    }
+
+    Ok((id, capacity, size))
+}
+
+/// Log storage directory of a partition, i.e. all `*.data` segment files ordered by segment id.
+#[derive(Debug)]
+pub struct LogDirectory {
+    pub segments: Vec<LogSegment>,
+}
+
+impl LogDirectory {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        let mut segments = vec![];
+        for entry in fs::read_dir(path)? {
+            let path = entry?.path();
+            if path.extension() == Some(OsStr::new("data")) {
+                segments.push(LogSegment::open(path)?);
+            }
+        }
+        segments.sort_by_key(|segment| segment.id);
+
+        Ok(LogDirectory { segments })
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = Result<LogRecord, io::Error>> + '_ {
+        self.segments
+            .iter()
+            .flat_map(|segment| {
+                let (events, error) = match segment.events() {
+                    Ok(events) => (Some(events), None),
+                    Err(e) => (None, Some(Err(e))),
+                };
+                events.into_iter().flatten().chain(error)
+            })
+            .map(|event| event.and_then(LoggedEvent::into_record))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_logged_events_truncated() {
+        let mut buffer = vec![];
+        buffer.extend_from_slice(&[60, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        buffer.extend_from_slice(&[0; 20]);
+
+        let mut events = logged_events(&buffer);
+        assert_eq!(io::ErrorKind::UnexpectedEof, events.next().unwrap().unwrap_err().kind());
+        assert!(events.next().is_none());
+    }
+
+    #[test]
+    fn test_logged_events_metadata_exceeds_frame() {
+        let mut buffer = vec![];
+        buffer.extend_from_slice(&[40, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        buffer.extend_from_slice(&[0; 38]);
+        buffer.extend_from_slice(&[8, 0]);
+        buffer.extend_from_slice(&[0; 6]);
+
+        let error = logged_events(&buffer).next().unwrap().unwrap_err();
+        assert_eq!("Metadata length 8 exceeds frame length 40", error.to_string());
+    }
+
+    #[test]
+    fn test_logged_events_frame_too_large() {
+        let mut buffer = vec![];
+        buffer.extend_from_slice(&[0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0, 0, 0, 0]);
+        buffer.extend_from_slice(&[0; 40]);
+
+        let error = logged_events(&buffer).next().unwrap().unwrap_err();
+        assert_eq!("Frame length 4294967295 exceeds maximum frame length 524288", error.to_string());
+    }
+
+    #[test]
+    fn test_logged_events_value_truncated() {
+        let segment = include_bytes!("../tests/log/02.data");
+        let length = u32::from_reader(&mut &segment[SEGMENT_METADATA_LENGTH..]).unwrap() as usize;
+        let frame = &segment[SEGMENT_METADATA_LENGTH..SEGMENT_METADATA_LENGTH + 12 + length - 10];
+
+        let error = logged_events(frame).next().unwrap().unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, error.kind());
+        assert_eq!("Expected value of 205 bytes but only 195 remaining", error.to_string());
+    }
+}
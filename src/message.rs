@@ -161,6 +161,49 @@ impl TaskEvent {
     }
 }
 
+pub const SYSTEM_TOPIC: &'static str = "internal-system";
+pub const SYSTEM_PARTITION: u16 = 0;
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+#[serde(rename_all = "camelCase")]
+pub struct PartitionsRequest {}
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+#[serde(rename_all = "camelCase")]
+pub struct PartitionsResponse {
+    pub partitions: Vec<Partition>,
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+#[serde(rename_all = "camelCase")]
+pub struct Partition {
+    pub topic: String,
+    pub id: u16,
+}
+
+pub const CREATING_STATE: &'static str = "CREATING";
+pub const CREATE_REJECTED_STATE: &'static str = "CREATE_REJECTED";
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicEvent {
+    pub state: String,
+    pub name: String,
+    pub partitions: i32,
+    pub replication_factor: i32,
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+#[serde(rename_all = "camelCase")]
+pub struct RaftEvent {
+    pub members: Vec<SocketAddress>,
+}
+
 pub const CREATE_DEPLOYMENT_STATE: &'static str = "CREATE_DEPLOYMENT";
 pub const DEPLOYMENT_CREATED_STATE: &'static str = "DEPLOYMENT_CREATED";
 pub const DEPLOYMENT_REJECTED_STATE: &'static str = "DEPLOYMENT_REJECTED";
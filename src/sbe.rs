@@ -1,8 +1,13 @@
-use io::{Data, FromBytes, HasBlockLength, HasData, HasMessageLength, Message, ToBytes, ToData};
-use message::{COMPLETE_STATE, NIL, TaskEvent};
-use std;
-
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength)]
+use alloc::string::String;
+use alloc::vec::Vec;
+use io::{self, ConstBlockLength, ConstMessage, Data, HasBlockLength, Message};
+#[cfg(feature = "std")]
+use io::ToData;
+#[cfg(feature = "std")]
+use message::{COMPLETE_STATE, CREATE_STATE, NIL, SYSTEM_PARTITION, SYSTEM_TOPIC, TaskEvent, TopicEvent};
+use schema::Schema;
+
+#[derive(Debug, PartialEq, Eq, FromBytes, ToBytes, HasBlockLength, Describe)]
 pub struct MessageHeader {
     pub block_length: u16,
     pub template_id: u16,
@@ -10,8 +15,17 @@ pub struct MessageHeader {
     pub version: u16,
 }
 
+impl MessageHeader {
+    // messages of all versions share template and schema id, the version only selects the fields
+    pub fn is_message<T: Message>(&self) -> bool {
+        self.template_id == T::template_id() && self.schema_id == T::schema_id()
+    }
+}
+
 pub trait ToMessageHeader {
     fn message_header() -> MessageHeader;
+
+    fn message_header_version(version: u16) -> MessageHeader;
 }
 
 impl<'a, T: Message + HasBlockLength> From<&'a T> for MessageHeader {
@@ -29,9 +43,32 @@ impl<T: Message + HasBlockLength> ToMessageHeader for T {
             version: T::version(),
         }
     }
+
+    fn message_header_version(version: u16) -> MessageHeader {
+        MessageHeader {
+            block_length: T::block_length_version(version),
+            template_id: T::template_id(),
+            schema_id: T::schema_id(),
+            version,
+        }
+    }
+}
+
+/// Message header usable in const contexts, i.e. as pattern of a `match` arm.
+pub trait ConstMessageHeader {
+    const HEADER: MessageHeader;
 }
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+impl<T: ConstMessage + ConstBlockLength> ConstMessageHeader for T {
+    const HEADER: MessageHeader = MessageHeader {
+        block_length: T::BLOCK_LENGTH,
+        template_id: T::TEMPLATE_ID,
+        schema_id: T::SCHEMA_ID,
+        version: T::VERSION,
+    };
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength, Describe)]
 pub enum ErrorCode {
     MessageNotSupported,
     TopicNotFound,
@@ -42,7 +79,7 @@ pub enum ErrorCode {
     InvalidMessage,
 }
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, Message, HasData, HasMessageLength)]
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasData, HasMessageLength, Describe)]
 #[message(template_id = "0", schema_id = "0", version = "1")]
 #[data = "error_data"]
 pub struct ErrorResponse {
@@ -52,17 +89,19 @@ pub struct ErrorResponse {
 }
 
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength, Describe)]
 pub enum ControlMessageType {
     AddTaskSubscription,
     RemoveTaskSubscription,
     IncreaseTaskSubscriptionCredits,
     RemoveTopicSubscription,
     RequestTopology,
+    RequestPartitions,
 }
 
+#[cfg(feature = "std")]
 impl ControlMessageType {
-    pub fn with<D: ToData>(self, data: &D) -> Result<ControlMessageRequest, std::io::Error> {
+    pub fn with<D: ToData>(self, data: &D) -> Result<ControlMessageRequest, io::Error> {
         Ok(ControlMessageRequest {
             message_type: self,
             data: data.to_data()?,
@@ -70,20 +109,20 @@ impl ControlMessageType {
     }
 }
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, Message, HasData, HasMessageLength)]
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasData, HasMessageLength, Describe)]
 #[message(template_id = "10", schema_id = "0", version = "1")]
 pub struct ControlMessageRequest {
     pub message_type: ControlMessageType,
     pub data: Data,
 }
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, Message, HasData, HasMessageLength)]
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasData, HasMessageLength, Describe)]
 #[message(template_id = "11", schema_id = "0", version = "1")]
 pub struct ControlMessageResponse {
     pub data: Data,
 }
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength, Describe)]
 pub enum EventType {
     TaskEvent,
     RaftEvent,
@@ -94,9 +133,11 @@ pub enum EventType {
     IncidentEvent,
     WorkflowEvent,
     NoopEvent,
+    TopicEvent,
+    PartitionEvent,
 }
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, Message, HasData, HasMessageLength)]
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasData, HasMessageLength, Describe)]
 #[message(template_id = "20", schema_id = "0", version = "1")]
 #[data = "command"]
 pub struct ExecuteCommandRequest {
@@ -108,8 +149,9 @@ pub struct ExecuteCommandRequest {
     pub command: Data,
 }
 
+#[cfg(feature = "std")]
 impl ExecuteCommandRequest {
-    pub fn complete_task(message: &SubscribedEvent, mut event: TaskEvent) -> Result<Self, std::io::Error> {
+    pub fn complete_task(message: &SubscribedEvent, mut event: TaskEvent) -> Result<Self, io::Error> {
         event.state = COMPLETE_STATE.into();
         if event.payload.is_empty() {
             event.payload = NIL.to_vec().into();
@@ -124,9 +166,27 @@ impl ExecuteCommandRequest {
             command,
         })
     }
+
+    /// Creates a topic, topics are managed on the system topic.
+    pub fn create_topic<S: Into<String>>(name: S, partitions: i32, replication_factor: i32) -> Result<Self, io::Error> {
+        let event = TopicEvent {
+            state: CREATE_STATE.into(),
+            name: name.into(),
+            partitions,
+            replication_factor,
+        };
+        Ok(ExecuteCommandRequest {
+            topic_name: SYSTEM_TOPIC.into(),
+            partition_id: SYSTEM_PARTITION,
+            position: 0,
+            key: 0,
+            event_type: EventType::TopicEvent,
+            command: event.to_data()?,
+        })
+    }
 }
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, Message, HasData, HasMessageLength)]
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasData, HasMessageLength, Describe)]
 #[message(template_id = "21", schema_id = "0", version = "1")]
 #[data = "event"]
 pub struct ExecuteCommandResponse {
@@ -137,13 +197,13 @@ pub struct ExecuteCommandResponse {
     pub event: Data,
 }
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength, Describe)]
 pub enum SubscriptionType {
     TaskSubscription,
     TopicSubscription,
 }
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, Message, HasData, HasMessageLength)]
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasData, HasMessageLength, Describe)]
 #[message(template_id = "30", schema_id = "0", version = "1")]
 #[data = "event"]
 pub struct SubscribedEvent {
@@ -157,7 +217,139 @@ pub struct SubscribedEvent {
     pub event: Data,
 }
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, Message, HasData, HasMessageLength)]
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, Message, HasMessageLength, Describe)]
+#[message(template_id = "200", schema_id = "0", version = "1")]
+pub struct BrokerEventMetadata {
+    pub request_stream_id: Option<i32>,
+    pub request_id: Option<u64>,
+    pub raft_term: i32,
+    pub subscriber_key: Option<u64>,
+    pub protocol_version: u16,
+    pub event_type: EventType,
+    pub incident_key: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength, Describe)]
+pub enum BooleanType {
+    False,
+    True,
+}
+
+impl From<bool> for BooleanType {
+    fn from(value: bool) -> Self {
+        if value { BooleanType::True } else { BooleanType::False }
+    }
+}
+
+impl From<BooleanType> for bool {
+    fn from(value: BooleanType) -> Self {
+        value == BooleanType::True
+    }
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength, Describe)]
+pub struct RaftMember {
+    pub port: u16,
+    pub host: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength, Describe)]
+#[message(template_id = "0", schema_id = "4", version = "1")]
+pub struct JoinRequest {
+    pub partition_id: u16,
+    pub term: u16,
+    pub port: u16,
+    pub topic_name: String,
+    pub host: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength, Describe)]
+#[message(template_id = "1", schema_id = "4", version = "1")]
+pub struct JoinResponse {
+    pub term: u16,
+    pub succeeded: BooleanType,
+    pub configuration_entry_position: u64,
+    pub configuration_entry_term: i32,
+    pub members: Vec<RaftMember>,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength, Describe)]
+#[message(template_id = "2", schema_id = "4", version = "1")]
+pub struct LeaveRequest {
+    pub partition_id: u16,
+    pub term: u16,
+    pub port: u16,
+    pub topic_name: String,
+    pub host: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength, Describe)]
+#[message(template_id = "3", schema_id = "4", version = "1")]
+pub struct LeaveResponse {
+    pub term: u16,
+    pub succeeded: BooleanType,
+    pub configuration_entry_position: u64,
+    pub configuration_entry_term: i32,
+    pub members: Vec<RaftMember>,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength, Describe)]
+#[message(template_id = "4", schema_id = "4", version = "1")]
+pub struct ConfigurationRequest {
+    pub partition_id: u16,
+    pub term: u16,
+    pub configuration_entry_position: u64,
+    pub configuration_entry_term: i32,
+    pub members: Vec<RaftMember>,
+    pub topic_name: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength, Describe)]
+#[message(template_id = "5", schema_id = "4", version = "1")]
+pub struct ConfigurationResponse {
+    pub term: u16,
+    pub succeeded: BooleanType,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength, Describe)]
+#[message(template_id = "6", schema_id = "4", version = "1")]
+pub struct PollRequest {
+    pub partition_id: u16,
+    pub term: u16,
+    pub last_event_position: u64,
+    pub last_event_term: i32,
+    pub port: u16,
+    pub topic_name: String,
+    pub host: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength, Describe)]
+#[message(template_id = "7", schema_id = "4", version = "1")]
+pub struct PollResponse {
+    pub term: u16,
+    pub granted: BooleanType,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength, Describe)]
+#[message(template_id = "8", schema_id = "4", version = "1")]
+pub struct VoteRequest {
+    pub partition_id: u16,
+    pub term: u16,
+    pub last_event_position: u64,
+    pub last_event_term: i32,
+    pub port: u16,
+    pub topic_name: String,
+    pub host: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength, Describe)]
+#[message(template_id = "9", schema_id = "4", version = "1")]
+pub struct VoteResponse {
+    pub term: u16,
+    pub granted: BooleanType,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasData, HasMessageLength, Describe)]
 #[message(template_id = "10", schema_id = "4", version = "1")]
 pub struct AppendRequest {
     pub partition_id: u16,
@@ -171,10 +363,110 @@ pub struct AppendRequest {
     pub data: Data,
 }
 
-#[cfg(test)]
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength, Describe)]
+#[message(template_id = "11", schema_id = "4", version = "1")]
+pub struct AppendResponse {
+    pub partition_id: u16,
+    pub term: u16,
+    pub succeeded: BooleanType,
+    pub previous_event_position: u64,
+    pub port: u16,
+    pub topic_name: String,
+    pub host: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength, Describe)]
+pub enum GossipEventType {
+    Ping,
+    Ack,
+    PingReq,
+    SyncRequest,
+    SyncResponse,
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength, Describe)]
+pub enum MembershipEventType {
+    Join,
+    Suspect,
+    Alive,
+    Confirm,
+    Leave,
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength, Describe)]
+pub struct MembershipEvent {
+    pub event_type: MembershipEventType,
+    pub gossip_epoch: u64,
+    pub gossip_heartbeat: u64,
+    pub port: u16,
+    pub host: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength, Describe)]
+pub struct CustomEvent {
+    pub sender_gossip_epoch: u64,
+    pub sender_gossip_heartbeat: u64,
+    pub sender_port: u16,
+    pub sender_host: String,
+    pub event_type: String,
+    pub payload: Data,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength, Describe)]
+#[message(template_id = "0", schema_id = "5", version = "1")]
+pub struct GossipEvent {
+    pub event_type: GossipEventType,
+    pub probe_member_port: u16,
+    pub sender_port: u16,
+    pub membership_events: Vec<MembershipEvent>,
+    pub custom_events: Vec<CustomEvent>,
+    pub probe_member_host: String,
+    pub sender_host: String,
+}
+
+pub fn client_schema() -> Result<Schema, io::Error> {
+    Schema::new("io.zeebe.protocol.clientapi", 0, 1)
+        .semantic_version("0.1.0")
+        .description("Zeebe Client Protocol")
+        .message::<ErrorResponse>()?
+        .message::<ControlMessageRequest>()?
+        .message::<ControlMessageResponse>()?
+        .message::<ExecuteCommandRequest>()?
+        .message::<ExecuteCommandResponse>()?
+        .message::<SubscribedEvent>()?
+        .message::<BrokerEventMetadata>()
+}
+
+pub fn raft_schema() -> Result<Schema, io::Error> {
+    Schema::new("io.zeebe.raft.protocol", 4, 1)
+        .semantic_version("0.1.0")
+        .description("Zeebe Raft Protocol")
+        .message::<JoinRequest>()?
+        .message::<JoinResponse>()?
+        .message::<LeaveRequest>()?
+        .message::<LeaveResponse>()?
+        .message::<ConfigurationRequest>()?
+        .message::<ConfigurationResponse>()?
+        .message::<PollRequest>()?
+        .message::<PollResponse>()?
+        .message::<VoteRequest>()?
+        .message::<VoteResponse>()?
+        .message::<AppendRequest>()?
+        .message::<AppendResponse>()
+}
+
+pub fn gossip_schema() -> Result<Schema, io::Error> {
+    Schema::new("io.zeebe.gossip.protocol", 5, 1)
+        .semantic_version("0.1.0")
+        .description("Zeebe Gossip Protocol")
+        .message::<GossipEvent>()
+}
+
+#[cfg(all(test, feature = "std"))]
 mod test {
     use super::*;
     use byteorder::{LittleEndian, WriteBytesExt};
+    use io::{FromBytes, HasMessageLength, ToBytes};
     use std::io::Write;
 
     #[test]
@@ -203,6 +495,36 @@ mod test {
         assert_eq!(8, MessageHeader::block_length());
     }
 
+    #[test]
+    fn test_message_header_version() {
+        let mut header = ExecuteCommandRequest::message_header_version(0);
+        assert_eq!(0, header.version);
+        assert!(header.is_message::<ExecuteCommandRequest>());
+        assert!(!header.is_message::<ExecuteCommandResponse>());
+
+        header.version = 42;
+        assert!(header.is_message::<ExecuteCommandRequest>());
+    }
+
+    #[test]
+    fn test_const_message_header() {
+        assert_eq!(ErrorResponse::message_header(), ErrorResponse::HEADER);
+        assert_eq!(ControlMessageRequest::message_header(), ControlMessageRequest::HEADER);
+        assert_eq!(ControlMessageResponse::message_header(), ControlMessageResponse::HEADER);
+        assert_eq!(ExecuteCommandRequest::message_header(), ExecuteCommandRequest::HEADER);
+        assert_eq!(ExecuteCommandResponse::message_header(), ExecuteCommandResponse::HEADER);
+        assert_eq!(SubscribedEvent::message_header(), SubscribedEvent::HEADER);
+        assert_eq!(AppendRequest::message_header(), AppendRequest::HEADER);
+
+        const HEADER: MessageHeader = ExecuteCommandResponse::HEADER;
+        let name = match HEADER {
+            ExecuteCommandRequest::HEADER => "request",
+            ExecuteCommandResponse::HEADER => "response",
+            _ => "unknown",
+        };
+        assert_eq!("response", name);
+    }
+
     #[test]
     fn test_control_message_request() {
         let mut buffer = vec![];
@@ -443,4 +765,192 @@ mod test {
             AppendRequest::message_header()
         );
     }
+
+    #[test]
+    fn test_create_topic() {
+        use io::FromData;
+        use message::{Partition, PartitionsRequest, PartitionsResponse};
+
+        let request = ExecuteCommandRequest::create_topic("orders", 3, 2).unwrap();
+        assert_eq!(SYSTEM_TOPIC, request.topic_name);
+        assert_eq!(SYSTEM_PARTITION, request.partition_id);
+        assert_eq!(EventType::TopicEvent, request.event_type);
+        assert_eq!(
+            TopicEvent {
+                state: CREATE_STATE.into(),
+                name: "orders".into(),
+                partitions: 3,
+                replication_factor: 2,
+            },
+            TopicEvent::from_data(&request).unwrap()
+        );
+
+        let request = ControlMessageType::RequestPartitions.with(&PartitionsRequest {}).unwrap();
+        assert_eq!(ControlMessageType::RequestPartitions, request.message_type);
+
+        let partitions = PartitionsResponse {
+            partitions: vec![
+                Partition {
+                    topic: "orders".into(),
+                    id: 1,
+                },
+            ],
+        };
+        let response = ControlMessageResponse { data: partitions.to_data().unwrap() };
+        assert_eq!(partitions, PartitionsResponse::from_data(&response).unwrap());
+    }
+
+    #[test]
+    fn test_join_response() {
+        let mut buffer = vec![];
+
+        buffer.write_u16::<LittleEndian>(2).unwrap();
+        buffer.write_u8(1).unwrap();
+        buffer.write_u64::<LittleEndian>(3).unwrap();
+        buffer.write_i32::<LittleEndian>(4).unwrap();
+        buffer.write_u16::<LittleEndian>(2).unwrap();
+        buffer.write_u8(2).unwrap();
+        buffer.write_u16::<LittleEndian>(8001).unwrap();
+        buffer.write_u16::<LittleEndian>(3).unwrap();
+        buffer.write_all("foo".as_bytes()).unwrap();
+        buffer.write_u16::<LittleEndian>(8002).unwrap();
+        buffer.write_u16::<LittleEndian>(3).unwrap();
+        buffer.write_all("bar".as_bytes()).unwrap();
+
+        let response = JoinResponse {
+            term: 2,
+            succeeded: true.into(),
+            configuration_entry_position: 3,
+            configuration_entry_term: 4,
+            members: vec![
+                RaftMember {
+                    port: 8001,
+                    host: "foo".into(),
+                },
+                RaftMember {
+                    port: 8002,
+                    host: "bar".into(),
+                },
+            ],
+        };
+
+        let mut bytes = vec![];
+        response.to_bytes(&mut bytes).unwrap();
+
+        assert_eq!(buffer, bytes);
+        assert_eq!(response, JoinResponse::from_bytes(&mut &buffer[..]).unwrap());
+        assert_eq!(buffer.len() as u32, response.message_length());
+
+        assert_eq!(
+            MessageHeader {
+                block_length: 15,
+                template_id: 1,
+                schema_id: 4,
+                version: 1,
+            },
+            JoinResponse::message_header()
+        );
+    }
+
+    #[test]
+    fn test_vote_request() {
+        let mut buffer = vec![];
+
+        buffer.write_u16::<LittleEndian>(1).unwrap();
+        buffer.write_u16::<LittleEndian>(2).unwrap();
+        buffer.write_u64::<LittleEndian>(3).unwrap();
+        buffer.write_i32::<LittleEndian>(4).unwrap();
+        buffer.write_u16::<LittleEndian>(5).unwrap();
+        buffer.write_u16::<LittleEndian>(3).unwrap();
+        buffer.write_all("foo".as_bytes()).unwrap();
+        buffer.write_u16::<LittleEndian>(3).unwrap();
+        buffer.write_all("bar".as_bytes()).unwrap();
+
+        let request = VoteRequest {
+            partition_id: 1,
+            term: 2,
+            last_event_position: 3,
+            last_event_term: 4,
+            port: 5,
+            topic_name: "foo".into(),
+            host: "bar".into(),
+        };
+
+        let mut bytes = vec![];
+        request.to_bytes(&mut bytes).unwrap();
+
+        assert_eq!(buffer, bytes);
+        assert_eq!(request, VoteRequest::from_bytes(&mut &buffer[..]).unwrap());
+
+        let response = VoteResponse::from_bytes(&mut &[2, 0, 1][..]).unwrap();
+        assert_eq!(2, response.term);
+        assert!(bool::from(response.granted));
+
+        assert_eq!(8, VoteRequest::template_id());
+        assert_eq!(9, VoteResponse::template_id());
+        assert_eq!(6, PollRequest::template_id());
+        assert_eq!(11, AppendResponse::template_id());
+    }
+
+    #[test]
+    fn test_broker_event_metadata() {
+        let metadata = BrokerEventMetadata {
+            request_stream_id: Some(3),
+            request_id: None,
+            raft_term: 1,
+            subscriber_key: Some(7),
+            protocol_version: 1,
+            event_type: EventType::TaskEvent,
+            incident_key: None,
+        };
+
+        let mut bytes = vec![];
+        metadata.to_bytes(&mut bytes).unwrap();
+        assert_eq!(BrokerEventMetadata::block_length() as usize, bytes.len());
+        assert_eq!(&[3, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], &bytes[..12]);
+        assert_eq!(&[0xff; 8], &bytes[bytes.len() - 8..]);
+        assert_eq!(metadata, BrokerEventMetadata::from_bytes(&mut &bytes[..]).unwrap());
+    }
+
+    #[test]
+    fn test_gossip_event() {
+        let event = GossipEvent {
+            event_type: GossipEventType::SyncResponse,
+            probe_member_port: 0,
+            sender_port: 51016,
+            membership_events: vec![
+                MembershipEvent {
+                    event_type: MembershipEventType::Suspect,
+                    gossip_epoch: 1,
+                    gossip_heartbeat: 2,
+                    port: 51017,
+                    host: "broker-1".into(),
+                },
+            ],
+            custom_events: vec![
+                CustomEvent {
+                    sender_gossip_epoch: 3,
+                    sender_gossip_heartbeat: 4,
+                    sender_port: 51016,
+                    sender_host: "broker-0".into(),
+                    event_type: "topology".into(),
+                    payload: vec![1, 2, 3].into(),
+                },
+            ],
+            probe_member_host: "".into(),
+            sender_host: "broker-0".into(),
+        };
+
+        let mut bytes = vec![];
+        event.to_bytes(&mut bytes).unwrap();
+        assert_eq!(event.message_length() as usize, bytes.len());
+        assert_eq!(&[4, 0, 0, 0x48, 0xc7, 19, 0, 1, 1], &bytes[..9]);
+
+        let header = GossipEvent::message_header();
+        assert_eq!(5, header.schema_id);
+        match ::RequestResponseMessage::read(&header, &mut &bytes[..]).unwrap() {
+            ::RequestResponseMessage::GossipEvent(decoded) => assert_eq!(event, decoded),
+            other => panic!("Unexpected message {:?}", other),
+        }
+    }
 }
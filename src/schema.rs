@@ -0,0 +1,598 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use io::{CharArray, Data, Error, ErrorKind, Message};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Primitive {
+    Char,
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Int64,
+    UInt64,
+}
+
+impl Primitive {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Primitive::Char => "char",
+            Primitive::Int8 => "int8",
+            Primitive::UInt8 => "uint8",
+            Primitive::Int16 => "int16",
+            Primitive::UInt16 => "uint16",
+            Primitive::Int32 => "int32",
+            Primitive::UInt32 => "uint32",
+            Primitive::Int64 => "int64",
+            Primitive::UInt64 => "uint64",
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        match *self {
+            Primitive::Char | Primitive::Int8 | Primitive::UInt8 => 1,
+            Primitive::Int16 | Primitive::UInt16 => 2,
+            Primitive::Int32 | Primitive::UInt32 => 4,
+            Primitive::Int64 | Primitive::UInt64 => 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeDescriptor {
+    Primitive(Primitive),
+    Array(Box<TypeDescriptor>, usize),
+    Optional(Box<TypeDescriptor>),
+    Enum(EnumDescriptor),
+    Set(SetDescriptor),
+    Composite(CompositeDescriptor),
+    Group(Box<TypeDescriptor>),
+    Data,
+    String,
+}
+
+impl TypeDescriptor {
+    /// Encoded length of fixed-length types, `None` for groups and var data.
+    pub fn block_length(&self) -> Option<usize> {
+        match *self {
+            TypeDescriptor::Primitive(primitive) => Some(primitive.size()),
+            TypeDescriptor::Array(ref element, length) => element.block_length().map(|size| size * length),
+            TypeDescriptor::Optional(ref inner) => inner.block_length(),
+            TypeDescriptor::Enum(ref descriptor) => Some(descriptor.encoding.size()),
+            TypeDescriptor::Set(ref descriptor) => Some(descriptor.encoding.size()),
+            TypeDescriptor::Composite(ref descriptor) => Some(descriptor.block_length()),
+            TypeDescriptor::Group(_) | TypeDescriptor::Data | TypeDescriptor::String => None,
+        }
+    }
+
+    /// Name of enums, sets and composites.
+    pub fn name(&self) -> Option<&'static str> {
+        match *self {
+            TypeDescriptor::Enum(ref descriptor) => Some(descriptor.name),
+            TypeDescriptor::Set(ref descriptor) => Some(descriptor.name),
+            TypeDescriptor::Composite(ref descriptor) => Some(descriptor.name),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumDescriptor {
+    pub name: &'static str,
+    pub encoding: Primitive,
+    pub values: Vec<(&'static str, u64)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetDescriptor {
+    pub name: &'static str,
+    pub encoding: Primitive,
+    pub choices: Vec<(&'static str, u8)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDescriptor {
+    pub name: &'static str,
+    pub ty: TypeDescriptor,
+    /// Offset in the block of the enclosing type, `None` for groups and var data.
+    pub offset: Option<usize>,
+    pub since_version: Option<u16>,
+    pub null_value: Option<&'static str>,
+}
+
+impl FieldDescriptor {
+    /// Encoded length of fixed-length fields, `None` for groups and var data.
+    pub fn width(&self) -> Option<usize> {
+        self.ty.block_length()
+    }
+
+    pub fn is_var_length(&self) -> bool {
+        self.width().is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositeDescriptor {
+    pub name: &'static str,
+    pub fields: Vec<FieldDescriptor>,
+}
+
+impl CompositeDescriptor {
+    pub fn new(name: &'static str, mut fields: Vec<FieldDescriptor>) -> Self {
+        let mut offset = 0;
+        for field in &mut fields {
+            field.offset = field.ty.block_length().map(|length| {
+                                                           offset += length;
+                                                           offset - length
+                                                       });
+        }
+
+        CompositeDescriptor { name, fields }
+    }
+
+    pub fn block_length(&self) -> usize {
+        self.fields.iter().filter_map(|field| field.ty.block_length()).sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageDescriptor {
+    pub template_id: u16,
+    pub schema_id: u16,
+    pub version: u16,
+    pub composite: CompositeDescriptor,
+}
+
+impl MessageDescriptor {
+    pub fn of<T: Message + Describe>() -> Result<Self, Error> {
+        match T::describe() {
+            TypeDescriptor::Composite(composite) => {
+                Ok(MessageDescriptor {
+                       template_id: T::template_id(),
+                       schema_id: T::schema_id(),
+                       version: T::version(),
+                       composite,
+                   })
+            }
+            other => Err(invalid(format!("Message must be a struct, found {:?}", other))),
+        }
+    }
+}
+
+/// Describes the SBE encoding of a type, see `#[derive(Describe)]`.
+pub trait Describe {
+    fn describe() -> TypeDescriptor;
+
+    /// Field descriptors of structs, empty for all other types.
+    fn fields() -> Vec<FieldDescriptor> {
+        match Self::describe() {
+            TypeDescriptor::Composite(composite) => composite.fields,
+            _ => Vec::new(),
+        }
+    }
+}
+
+macro_rules! impl_describe {
+    ($($t:ty => $primitive:ident),*) => {
+        $(
+        impl Describe for $t {
+            fn describe() -> TypeDescriptor {
+                TypeDescriptor::Primitive(Primitive::$primitive)
+            }
+        }
+        )*
+    }
+}
+
+impl_describe!(u8 => UInt8, i8 => Int8, u16 => UInt16, i16 => Int16, u32 => UInt32, i32 => Int32, u64 => UInt64, i64 => Int64);
+
+impl<T: Describe> Describe for Option<T> {
+    fn describe() -> TypeDescriptor {
+        TypeDescriptor::Optional(Box::new(T::describe()))
+    }
+}
+
+impl<T: Describe, const N: usize> Describe for [T; N] {
+    fn describe() -> TypeDescriptor {
+        TypeDescriptor::Array(Box::new(T::describe()), N)
+    }
+}
+
+impl<const N: usize> Describe for CharArray<N> {
+    fn describe() -> TypeDescriptor {
+        TypeDescriptor::Array(Box::new(TypeDescriptor::Primitive(Primitive::Char)), N)
+    }
+}
+
+impl<T: Describe> Describe for Vec<T> {
+    fn describe() -> TypeDescriptor {
+        TypeDescriptor::Group(Box::new(T::describe()))
+    }
+}
+
+impl Describe for Data {
+    fn describe() -> TypeDescriptor {
+        TypeDescriptor::Data
+    }
+}
+
+impl Describe for String {
+    fn describe() -> TypeDescriptor {
+        TypeDescriptor::String
+    }
+}
+
+/// Collects message and type descriptors to export them as SBE XML schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+    pub package: &'static str,
+    pub id: u16,
+    pub version: u16,
+    pub semantic_version: Option<&'static str>,
+    pub description: Option<&'static str>,
+    pub types: Vec<TypeDescriptor>,
+    pub messages: Vec<MessageDescriptor>,
+}
+
+impl Schema {
+    pub fn new(package: &'static str, id: u16, version: u16) -> Self {
+        Schema {
+            package,
+            id,
+            version,
+            semantic_version: None,
+            description: None,
+            types: Vec::new(),
+            messages: Vec::new(),
+        }
+    }
+
+    pub fn semantic_version(mut self, semantic_version: &'static str) -> Self {
+        self.semantic_version = Some(semantic_version);
+        self
+    }
+
+    pub fn description(mut self, description: &'static str) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    pub fn with_type<T: Describe>(mut self) -> Self {
+        self.types.push(T::describe());
+        self
+    }
+
+    pub fn message<T: Message + Describe>(mut self) -> Result<Self, Error> {
+        let message = MessageDescriptor::of::<T>()?;
+        if message.schema_id != self.id {
+            return Err(invalid(format!("Message {} belongs to schema {} not {}", message.composite.name, message.schema_id, self.id)));
+        }
+        self.messages.push(message);
+        Ok(self)
+    }
+
+    pub fn to_xml(&self) -> Result<String, Error> {
+        let mut types = Vec::new();
+        for ty in &self.types {
+            collect_types(ty, &mut types);
+        }
+        for message in &self.messages {
+            for field in &message.composite.fields {
+                collect_types(&field.ty, &mut types);
+            }
+        }
+
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n");
+        xml.push_str("<sbe:messageSchema xmlns:sbe=\"http://fixprotocol.io/2016/sbe\"\n");
+        let _ = write!(xml, "                   package=\"{}\" id=\"{}\" version=\"{}\"", escape(self.package), self.id, self.version);
+        if let Some(semantic_version) = self.semantic_version {
+            let _ = write!(xml, " semanticVersion=\"{}\"", escape(semantic_version));
+        }
+        xml.push('\n');
+        xml.push_str("                   ");
+        if let Some(description) = self.description {
+            let _ = write!(xml, "description=\"{}\" ", escape(description));
+        }
+        xml.push_str("byteOrder=\"littleEndian\">\n");
+        xml.push_str("    <types>\n");
+
+        let fields = || self.messages.iter().flat_map(|message| &message.composite.fields);
+        if !self.messages.is_empty() {
+            xml.push_str("        <composite name=\"messageHeader\" description=\"Message identifiers and length of message root\">\n");
+            for name in &["blockLength", "templateId", "schemaId", "version"] {
+                let _ = writeln!(xml, "            <type name=\"{}\" primitiveType=\"uint16\"/>", name);
+            }
+            xml.push_str("        </composite>\n");
+        }
+        if fields().any(|field| uses(&field.ty, &|ty| matches!(*ty, TypeDescriptor::Group(_)))) {
+            xml.push_str("        <composite name=\"groupSizeEncoding\">\n");
+            xml.push_str("            <type name=\"blockLength\" primitiveType=\"uint16\"/>\n");
+            xml.push_str("            <type name=\"numInGroup\" primitiveType=\"uint8\"/>\n");
+            xml.push_str("        </composite>\n");
+        }
+        if fields().any(|field| uses(&field.ty, &|ty| *ty == TypeDescriptor::Data)) {
+            xml.push_str("        <composite name=\"varDataEncoding\">\n");
+            xml.push_str("            <type name=\"length\" primitiveType=\"uint16\"/>\n");
+            xml.push_str("            <type name=\"varData\" primitiveType=\"uint8\" length=\"0\"/>\n");
+            xml.push_str("        </composite>\n");
+        }
+        if fields().any(|field| uses(&field.ty, &|ty| *ty == TypeDescriptor::String)) {
+            xml.push_str("        <composite name=\"varStringEncoding\">\n");
+            xml.push_str("            <type name=\"length\" primitiveType=\"uint16\"/>\n");
+            xml.push_str("            <type name=\"varData\" primitiveType=\"uint8\" length=\"0\" characterEncoding=\"UTF-8\"/>\n");
+            xml.push_str("        </composite>\n");
+        }
+
+        for ty in types {
+            if !xml.ends_with("<types>\n") {
+                xml.push('\n');
+            }
+            write_type(&mut xml, ty)?;
+        }
+
+        xml.push_str("    </types>\n");
+
+        for message in &self.messages {
+            xml.push('\n');
+            let _ = writeln!(xml, "    <sbe:message name=\"{}\" id=\"{}\">", message.composite.name, message.template_id);
+            write_fields(&mut xml, 2, &message.composite.fields, &mut 0)?;
+            xml.push_str("    </sbe:message>\n");
+        }
+
+        xml.push_str("</sbe:messageSchema>\n");
+
+        Ok(xml)
+    }
+}
+
+// named types in dependency order, so every type is declared before it's referenced
+fn collect_types<'a>(ty: &'a TypeDescriptor, types: &mut Vec<&'a TypeDescriptor>) {
+    match *ty {
+        TypeDescriptor::Array(ref inner, _) | TypeDescriptor::Optional(ref inner) => collect_types(inner, types),
+        TypeDescriptor::Group(ref entry) => {
+            if let TypeDescriptor::Composite(ref composite) = **entry {
+                for field in &composite.fields {
+                    collect_types(&field.ty, types);
+                }
+            }
+        }
+        TypeDescriptor::Composite(ref composite) => {
+            for field in &composite.fields {
+                collect_types(&field.ty, types);
+            }
+        }
+        _ => {}
+    }
+
+    if ty.name().is_some() && !types.iter().any(|other| other.name() == ty.name()) {
+        types.push(ty);
+    }
+}
+
+fn uses(ty: &TypeDescriptor, predicate: &dyn Fn(&TypeDescriptor) -> bool) -> bool {
+    if predicate(ty) {
+        return true;
+    }
+
+    match *ty {
+        TypeDescriptor::Group(ref entry) => {
+            match **entry {
+                TypeDescriptor::Composite(ref composite) => composite.fields.iter().any(|field| uses(&field.ty, predicate)),
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+fn write_type(xml: &mut String, ty: &TypeDescriptor) -> Result<(), Error> {
+    match *ty {
+        TypeDescriptor::Enum(ref descriptor) => {
+            let _ = writeln!(xml, "        <enum name=\"{}\" encodingType=\"{}\">", descriptor.name, descriptor.encoding.name());
+            for &(name, value) in &descriptor.values {
+                let _ = writeln!(xml, "            <validValue name=\"{}\">{}</validValue>", constant_case(name), value);
+            }
+            xml.push_str("        </enum>\n");
+        }
+        TypeDescriptor::Set(ref descriptor) => {
+            let _ = writeln!(xml, "        <set name=\"{}\" encodingType=\"{}\">", descriptor.name, descriptor.encoding.name());
+            for &(name, bit) in &descriptor.choices {
+                let _ = writeln!(xml, "            <choice name=\"{}\">{}</choice>", camel_case(name), bit);
+            }
+            xml.push_str("        </set>\n");
+        }
+        TypeDescriptor::Composite(ref descriptor) => {
+            let _ = writeln!(xml, "        <composite name=\"{}\">", descriptor.name);
+            for field in &descriptor.fields {
+                let name = camel_case(field.name);
+                let since_version = field.since_version.map(|version| format!(" sinceVersion=\"{}\"", version)).unwrap_or_default();
+                match field.ty.name() {
+                    Some(ty) => {
+                        let _ = writeln!(xml, "            <ref name=\"{}\" type=\"{}\"{}/>", name, ty, since_version);
+                    }
+                    None => {
+                        let encoding = encoded_type(field)?;
+                        let _ = writeln!(xml, "            <type name=\"{}\" {}{}/>", name, encoding, since_version);
+                    }
+                }
+            }
+            xml.push_str("        </composite>\n");
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn write_fields(xml: &mut String, indent: usize, fields: &[FieldDescriptor], id: &mut u16) -> Result<(), Error> {
+    let padding = "    ".repeat(indent);
+
+    for field in fields {
+        *id += 1;
+        let name = camel_case(field.name);
+        let since_version = field.since_version.map(|version| format!(" sinceVersion=\"{}\"", version)).unwrap_or_default();
+
+        match field.ty {
+            TypeDescriptor::Group(ref entry) => {
+                let composite = match **entry {
+                    TypeDescriptor::Composite(ref composite) => composite,
+                    ref other => return Err(invalid(format!("Group {} must contain structs, found {:?}", field.name, other))),
+                };
+                let _ = writeln!(xml,
+                                 "{}<group name=\"{}\" id=\"{}\" dimensionType=\"groupSizeEncoding\"{}>",
+                                 padding,
+                                 name,
+                                 id,
+                                 since_version);
+                write_fields(xml, indent + 1, &composite.fields, id)?;
+                let _ = writeln!(xml, "{}</group>", padding);
+            }
+            TypeDescriptor::Data => {
+                let _ = writeln!(xml, "{}<data name=\"{}\" id=\"{}\" type=\"varDataEncoding\"{}/>", padding, name, id, since_version);
+            }
+            TypeDescriptor::String => {
+                let _ = writeln!(xml, "{}<data name=\"{}\" id=\"{}\" type=\"varStringEncoding\"{}/>", padding, name, id, since_version);
+            }
+            TypeDescriptor::Primitive(primitive) => {
+                let _ = writeln!(xml, "{}<field name=\"{}\" id=\"{}\" type=\"{}\"{}/>", padding, name, id, primitive.name(), since_version);
+            }
+            TypeDescriptor::Optional(ref inner) => {
+                let primitive = match **inner {
+                    TypeDescriptor::Primitive(primitive) => primitive,
+                    ref other => return Err(invalid(format!("Optional field {} must be primitive, found {:?}", field.name, other))),
+                };
+                let null_value = field.null_value.map(|null| format!(" nullValue=\"{}\"", escape(null))).unwrap_or_default();
+                let _ = writeln!(xml,
+                                 "{}<field name=\"{}\" id=\"{}\" type=\"{}\" presence=\"optional\"{}{}/>",
+                                 padding,
+                                 name,
+                                 id,
+                                 primitive.name(),
+                                 null_value,
+                                 since_version);
+            }
+            TypeDescriptor::Array(..) => {
+                return Err(invalid(format!("Array field {} must be declared in a composite", field.name)));
+            }
+            ref other => {
+                let _ = writeln!(xml,
+                                 "{}<field name=\"{}\" id=\"{}\" type=\"{}\"{}/>",
+                                 padding,
+                                 name,
+                                 id,
+                                 other.name().unwrap_or_default(),
+                                 since_version);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// attributes of an inline `<type>` in a composite
+fn encoded_type(field: &FieldDescriptor) -> Result<String, Error> {
+    match field.ty {
+        TypeDescriptor::Primitive(primitive) => Ok(format!("primitiveType=\"{}\"", primitive.name())),
+        TypeDescriptor::Array(ref element, length) => {
+            match **element {
+                TypeDescriptor::Primitive(Primitive::Char) => Ok(format!("primitiveType=\"char\" length=\"{}\" characterEncoding=\"US-ASCII\"", length)),
+                TypeDescriptor::Primitive(primitive) => Ok(format!("primitiveType=\"{}\" length=\"{}\"", primitive.name(), length)),
+                ref other => Err(invalid(format!("Array field {} must contain primitives, found {:?}", field.name, other))),
+            }
+        }
+        TypeDescriptor::Optional(ref inner) => {
+            match **inner {
+                TypeDescriptor::Primitive(primitive) => {
+                    let null_value = field.null_value.map(|null| format!(" nullValue=\"{}\"", escape(null))).unwrap_or_default();
+                    Ok(format!("primitiveType=\"{}\" presence=\"optional\"{}", primitive.name(), null_value))
+                }
+                ref other => Err(invalid(format!("Optional field {} must be primitive, found {:?}", field.name, other))),
+            }
+        }
+        ref other => Err(invalid(format!("Field {} can't be part of a composite, found {:?}", field.name, other))),
+    }
+}
+
+fn camel_case(name: &str) -> String {
+    let mut camel = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = !camel.is_empty();
+        } else if upper {
+            camel.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    camel
+}
+
+fn constant_case(name: &str) -> String {
+    let mut constant = String::with_capacity(name.len() + 4);
+    for (idx, c) in name.chars().enumerate() {
+        if c.is_uppercase() && idx > 0 {
+            constant.push('_');
+        }
+        constant.extend(c.to_uppercase());
+    }
+    constant
+}
+
+fn escape(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        assert_eq!("partitionId", camel_case("partition_id"));
+        assert_eq!("batchBegin", camel_case("batch_begin"));
+        assert_eq!("key", camel_case("key"));
+        assert_eq!("WORKFLOW_INSTANCE_EVENT", constant_case("WorkflowInstanceEvent"));
+        assert_eq!("KEEP_ALIVE", constant_case("KeepAlive"));
+    }
+
+    #[test]
+    fn test_offsets() {
+        let composite = CompositeDescriptor::new("Test",
+                                                 vec![FieldDescriptor {
+                                                          name: "a",
+                                                          ty: u16::describe(),
+                                                          offset: None,
+                                                          since_version: None,
+                                                          null_value: None,
+                                                      },
+                                                      FieldDescriptor {
+                                                          name: "b",
+                                                          ty: <[u32; 2]>::describe(),
+                                                          offset: None,
+                                                          since_version: None,
+                                                          null_value: None,
+                                                      },
+                                                      FieldDescriptor {
+                                                          name: "c",
+                                                          ty: String::describe(),
+                                                          offset: None,
+                                                          since_version: None,
+                                                          null_value: None,
+                                                      }]);
+
+        let offsets: Vec<_> = composite.fields.iter().map(|field| field.offset).collect();
+        assert_eq!(vec![Some(0), Some(2), None], offsets);
+        assert_eq!(10, composite.block_length());
+    }
+}
@@ -0,0 +1,319 @@
+use {RequestResponseMessage, TransportMessage};
+use frame::DEFAULT_MAX_FRAME_LENGTH;
+use io::{self, HasMessageLength, Read};
+use sbe::ToMessageHeader;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::sync::mpsc::{self, Receiver, RecvError, SendError, Sender, TryRecvError};
+
+/// Logical stream of a connection, received frames with its stream id are routed to it by the
+/// `Multiplexer` which opened it.
+#[derive(Debug)]
+pub struct Stream {
+    id: u32,
+    receiver: Receiver<TransportMessage>,
+}
+
+impl Stream {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn request<M: Into<RequestResponseMessage> + ToMessageHeader + HasMessageLength>(&self, request_id: u64, message: M) -> TransportMessage {
+        let mut request = TransportMessage::request(request_id, message);
+        request.frame_header_mut().stream_id = self.id;
+        request
+    }
+
+    pub fn recv(&self) -> Result<TransportMessage, RecvError> {
+        self.receiver.recv()
+    }
+
+    pub fn try_recv(&self) -> Result<TransportMessage, TryRecvError> {
+        self.receiver.try_recv()
+    }
+}
+
+/// Frame which couldn't be routed, its message is handed back to the caller, i.e. connection level
+/// frames of stream 0 like keep-alives and failures. Failed reads have no message.
+#[derive(Debug)]
+pub struct RouteError {
+    // boxed to keep the error small as routing succeeds most of the time
+    message: Option<Box<TransportMessage>>,
+    error: io::Error,
+}
+
+impl RouteError {
+    fn unrouted(message: TransportMessage, kind: io::ErrorKind, error: String) -> Self {
+        RouteError {
+            message: Some(Box::new(message)),
+            error: io::Error::new(kind, error),
+        }
+    }
+
+    pub fn kind(&self) -> io::ErrorKind {
+        self.error.kind()
+    }
+
+    pub fn message(&self) -> Option<&TransportMessage> {
+        self.message.as_deref()
+    }
+
+    pub fn into_message(self) -> Option<TransportMessage> {
+        self.message.map(|message| *message)
+    }
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl Error for RouteError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<io::Error> for RouteError {
+    fn from(error: io::Error) -> Self {
+        RouteError { message: None, error }
+    }
+}
+
+impl From<RouteError> for io::Error {
+    fn from(error: RouteError) -> Self {
+        error.error
+    }
+}
+
+#[derive(Debug)]
+struct Streams {
+    next_stream_id: u32,
+    senders: HashMap<u32, Sender<TransportMessage>>,
+}
+
+/// Routes the received frames of one connection to the open streams by their stream id, stream 0
+/// is left to frames which are not multiplexed. It can be shared with the thread reading the
+/// connection, i.e. in an `Arc`.
+#[derive(Debug)]
+pub struct Multiplexer {
+    streams: Mutex<Streams>,
+    max_frame_length: usize,
+}
+
+impl Default for Multiplexer {
+    fn default() -> Self {
+        Multiplexer {
+            streams: Mutex::new(Streams {
+                next_stream_id: 1,
+                senders: HashMap::new(),
+            }),
+            max_frame_length: DEFAULT_MAX_FRAME_LENGTH,
+        }
+    }
+}
+
+impl Multiplexer {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates a multiplexer which rejects received frames longer than `max_frame_length`.
+    pub fn with_max_frame_length(max_frame_length: usize) -> Self {
+        Multiplexer { max_frame_length, ..Default::default() }
+    }
+
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_length
+    }
+
+    /// Opens a stream with the next unused stream id, stream 0 is skipped after a wrap-around.
+    pub fn open(&self) -> Stream {
+        let mut streams = self.streams();
+        while streams.next_stream_id == 0 || streams.senders.contains_key(&streams.next_stream_id) {
+            streams.next_stream_id = streams.next_stream_id.wrapping_add(1);
+        }
+
+        let id = streams.next_stream_id;
+        streams.next_stream_id = id.wrapping_add(1);
+        Multiplexer::insert(&mut streams, id)
+    }
+
+    pub fn open_with_id(&self, id: u32) -> Result<Stream, io::Error> {
+        if id == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Stream 0 is reserved for frames which are not multiplexed"));
+        }
+
+        let mut streams = self.streams();
+        if streams.senders.contains_key(&id) {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("Stream {} is already open", id)));
+        }
+
+        Ok(Multiplexer::insert(&mut streams, id))
+    }
+
+    pub fn close(&self, id: u32) -> bool {
+        self.streams().senders.remove(&id).is_some()
+    }
+
+    pub fn is_open(&self, id: u32) -> bool {
+        self.streams().senders.contains_key(&id)
+    }
+
+    /// Passes the message to the stream of its stream id, fails if the stream isn't open or was
+    /// dropped, which also closes it. The message is handed back in the error in both cases.
+    pub fn route(&self, message: TransportMessage) -> Result<(), RouteError> {
+        let id = message.frame_header().stream_id;
+
+        let mut streams = self.streams();
+        let message = match streams.senders.get(&id) {
+            Some(sender) => {
+                match sender.send(message) {
+                    Ok(()) => return Ok(()),
+                    Err(SendError(message)) => message,
+                }
+            }
+            None => return Err(RouteError::unrouted(message, io::ErrorKind::NotFound, format!("Received frame for unknown stream {}", id))),
+        };
+
+        streams.senders.remove(&id);
+        Err(RouteError::unrouted(message, io::ErrorKind::BrokenPipe, format!("Stream {} was dropped", id)))
+    }
+
+    /// Reads the next frame of the connection and routes it, see `route`. Frames longer than the
+    /// maximum frame length are rejected, the connection has to be closed then.
+    pub fn route_from<R: Read + ?Sized>(&self, reader: &mut R) -> Result<(), RouteError> {
+        let message = TransportMessage::from_reader_limited(reader, self.max_frame_length)?;
+        self.route(message)
+    }
+
+    fn streams(&self) -> MutexGuard<'_, Streams> {
+        // the streams are consistent after each operation, so a panic of another thread doesn't matter
+        self.streams.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn insert(streams: &mut Streams, id: u32) -> Stream {
+        let (sender, receiver) = mpsc::channel();
+        streams.senders.insert(id, sender);
+
+        Stream { id, receiver }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use frame::ControlMessage;
+
+    fn message(stream_id: u32) -> TransportMessage {
+        let mut message = TransportMessage::control(ControlMessage::KeepAlive);
+        message.frame_header_mut().stream_id = stream_id;
+        message
+    }
+
+    #[test]
+    fn test_open() {
+        let multiplexer = Multiplexer::new();
+        let first = multiplexer.open();
+        let third = multiplexer.open_with_id(3).unwrap();
+        let second = multiplexer.open();
+        assert_eq!((1, 2, 3), (first.id(), second.id(), third.id()));
+        assert_eq!(4, multiplexer.open().id());
+
+        let error = multiplexer.open_with_id(2).unwrap_err();
+        assert_eq!("Stream 2 is already open", error.to_string());
+
+        assert!(multiplexer.close(2));
+        assert!(!multiplexer.is_open(2));
+        assert!(multiplexer.open_with_id(2).is_ok());
+
+        let error = multiplexer.open_with_id(0).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, error.kind());
+        assert!(!multiplexer.is_open(0));
+    }
+
+    #[test]
+    fn test_open_wraps_around() {
+        let multiplexer = Multiplexer::new();
+        let last = multiplexer.open_with_id(u32::MAX - 1).unwrap();
+        multiplexer.streams().next_stream_id = u32::MAX - 1;
+
+        assert_eq!(u32::MAX, multiplexer.open().id());
+        assert_eq!(1, multiplexer.open().id());
+        assert!(!multiplexer.is_open(0));
+        assert!(multiplexer.is_open(last.id()));
+    }
+
+    #[test]
+    fn test_route() {
+        let multiplexer = Multiplexer::new();
+        let first = multiplexer.open();
+        let second = multiplexer.open();
+
+        multiplexer.route(message(second.id())).unwrap();
+        multiplexer.route(message(first.id())).unwrap();
+        multiplexer.route(message(second.id())).unwrap();
+
+        assert_eq!(1, first.try_recv().unwrap().frame_header().stream_id);
+        assert!(first.try_recv().is_err());
+        assert_eq!(2, second.try_recv().unwrap().frame_header().stream_id);
+        assert_eq!(2, second.try_recv().unwrap().frame_header().stream_id);
+
+        let error = multiplexer.route(message(0)).unwrap_err();
+        assert_eq!("Received frame for unknown stream 0", error.to_string());
+    }
+
+    #[test]
+    fn test_route_connection_frame() {
+        let multiplexer = Multiplexer::new();
+        let stream = multiplexer.open();
+
+        let mut buffer = vec![];
+        message(0).write_to_vec(&mut buffer).unwrap();
+        let error = multiplexer.route_from(&mut &buffer[..]).unwrap_err();
+        assert_eq!(io::ErrorKind::NotFound, error.kind());
+        assert!(stream.try_recv().is_err());
+
+        // the keep alive of stream 0 is handed back to the caller
+        match error.into_message() {
+            Some(TransportMessage::ControlRequest(ref request)) => {
+                assert_eq!(0, request.frame_header.stream_id);
+                assert_eq!(ControlMessage::KeepAlive, *request.message());
+            }
+            other => panic!("Unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_route_frame_too_large() {
+        let multiplexer = Multiplexer::with_max_frame_length(4);
+        let stream = multiplexer.open();
+
+        let mut buffer = vec![];
+        message(stream.id()).write_to_vec(&mut buffer).unwrap();
+        let error = multiplexer.route_from(&mut &buffer[..]).unwrap_err();
+        assert_eq!("Frame length 6 exceeds maximum frame length 4", error.to_string());
+        assert!(stream.try_recv().is_err());
+
+        let multiplexer = Multiplexer::new();
+        let stream = multiplexer.open();
+        multiplexer.route_from(&mut &buffer[..]).unwrap();
+        assert!(stream.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_route_dropped_stream() {
+        let multiplexer = Multiplexer::new();
+        let stream = multiplexer.open();
+        drop(stream);
+
+        let error = multiplexer.route(message(1)).unwrap_err();
+        assert_eq!(io::ErrorKind::BrokenPipe, error.kind());
+        assert!(!multiplexer.is_open(1));
+        assert_eq!(1, error.message().unwrap().frame_header().stream_id);
+    }
+}
@@ -77,6 +77,50 @@ fn topology_request_read() {
     }
 }
 
+#[test]
+fn topology_request_newer_block_length() {
+    dump_vec!(dump, "topology-request.bin");
+    let mut frame = dump;
+
+    // a newer version appends three bytes of unknown fields to the block of the message
+    let mut message_header = MessageHeader::from_bytes(&mut &frame[22..]).unwrap();
+    message_header.block_length += 3;
+    message_header.to_bytes(&mut &mut frame[22..30]).unwrap();
+    let block_end = 30 + ControlMessageRequest::block_length() as usize;
+    frame.splice(block_end..block_end, vec![0xaa; 3]);
+
+    let mut data_frame_header = DataFrameHeader::from_bytes(&mut &frame[..]).unwrap();
+    data_frame_header.length += 3;
+    data_frame_header.to_bytes(&mut &mut frame[..12]).unwrap();
+    frame.resize(data_frame_header.aligned_length(), 0);
+
+    match TransportMessage::from_bytes(&mut &frame[..]).unwrap() {
+        TransportMessage::RequestResponse(request) => {
+            match *request.message() {
+                RequestResponseMessage::ControlMessageRequest(ref message) => {
+                    assert_eq!(ControlMessageType::RequestTopology, message.message_type);
+                    assert_eq!(TopologyRequest {}, TopologyRequest::from_data(message).unwrap());
+                }
+                ref message => panic!("Unexpected message {:?}", message),
+            }
+        }
+        message => panic!("Unexpected message {:?}", message),
+    }
+
+    match TransportMessageRef::from_bytes_ref(&mut &frame[..]).unwrap() {
+        TransportMessageRef::RequestResponse(request) => {
+            match request.message {
+                RequestResponseMessageRef::ControlMessageRequest(message) => {
+                    assert_eq!(ControlMessageType::RequestTopology, message.message_type);
+                    assert_eq!(TopologyRequest {}, TopologyRequest::from_data(&ControlMessageRequest::from(message)).unwrap());
+                }
+                message => panic!("Unexpected message {:?}", message),
+            }
+        }
+        message => panic!("Unexpected message {:?}", message),
+    }
+}
+
 #[test]
 fn topology_request_write() {
     dump_vec!(expected, "topology-request.bin");
@@ -93,6 +137,29 @@ fn topology_request_write() {
     assert_eq!(expected, buffer);
 }
 
+#[test]
+fn topology_request_write_to_slice() {
+    dump_vec!(expected, "topology-request.bin");
+
+    let message = ControlMessageType::RequestTopology
+        .with(&TopologyRequest {})
+        .unwrap();
+    let request = TransportMessage::request(256, message);
+
+    // dirty buffer to ensure the padding is zeroed
+    let mut buffer = vec![0xff; expected.len() + 4];
+    assert_eq!(expected.len(), request.write_to_slice(&mut buffer).unwrap());
+    assert_eq!(&expected[..], &buffer[..expected.len()]);
+
+    let mut too_small = vec![0; expected.len() - 1];
+    assert!(request.write_to_slice(&mut too_small).is_err());
+
+    let mut buffer = vec![];
+    request.write_to_vec(&mut buffer).unwrap();
+    assert_eq!(expected, buffer);
+    assert_eq!(expected.len(), buffer.capacity());
+}
+
 #[test]
 fn topology_response_manual() {
     dump!(reader, "topology-response.bin");
@@ -780,6 +847,21 @@ fn create_deployment_request() {
     assert_eq!(data_frame_header.padding(), reader.len());
 }
 
+#[test]
+fn create_deployment_request_write_vectored() {
+    dump_vec!(expected, "create-deployment-request.bin");
+
+    let request = TransportMessage::from_bytes(&mut &expected[..]).unwrap();
+
+    let mut buffer = vec![];
+    request.write_vectored(&mut buffer).unwrap();
+    assert_eq!(expected, buffer);
+
+    let mut buffer = vec![];
+    request.to_bytes(&mut buffer).unwrap();
+    assert_eq!(expected, buffer);
+}
+
 #[test]
 fn create_deployment_response() {
     dump!(reader, "create-deployment-response.bin");
@@ -1037,6 +1119,134 @@ fn keep_alive() {
     assert_eq!(data_frame_header.padding(), reader.len());
 }
 
+#[test]
+fn keep_alive_write() {
+    dump_vec!(expected, "keep-alive.bin");
+
+    let mut buffer = vec![];
+    TransportMessage::control(ControlMessage::KeepAlive).to_bytes(&mut buffer).unwrap();
+
+    assert_eq!(expected, buffer);
+}
+
+#[test]
+fn write_all_frame_types() {
+    let dumps: [&[u8]; 4] = [
+        include_bytes!("dumps/topology-request.bin"),
+        include_bytes!("dumps/task-subscription-locked-task.bin"),
+        include_bytes!("dumps/append-request.bin"),
+        include_bytes!("dumps/keep-alive.bin"),
+    ];
+
+    for dump in &dumps {
+        let message = TransportMessage::from_bytes(&mut &dump[..]).unwrap();
+
+        let mut buffer = vec![0xff; dump.len()];
+        assert_eq!(dump.len(), message.write_to_slice(&mut buffer).unwrap());
+        assert_eq!(*dump, &buffer[..]);
+
+        let mut buffer = vec![];
+        message.write_to_vec(&mut buffer).unwrap();
+        assert_eq!(*dump, &buffer[..]);
+
+        let mut buffer = vec![];
+        message.write_vectored(&mut buffer).unwrap();
+        assert_eq!(*dump, &buffer[..]);
+
+        let mut buffer = vec![];
+        message.to_bytes(&mut buffer).unwrap();
+        assert_eq!(*dump, &buffer[..]);
+    }
+
+    let failure = TransportMessage::failure(7, Data::from(vec![0xab; 300]));
+    let mut expected = vec![];
+    failure.write_to_vec(&mut expected).unwrap();
+
+    let mut buffer = vec![];
+    failure.write_vectored(&mut buffer).unwrap();
+    assert_eq!(expected, buffer);
+}
+
+#[test]
+fn topology_response_failed() {
+    dump_vec!(dump, "topology-response.bin");
+    let mut frame = dump;
+
+    let mut data_frame_header = DataFrameHeader::from_bytes(&mut &frame[..]).unwrap();
+    data_frame_header.set_failed(true);
+    data_frame_header.to_bytes(&mut &mut frame[..]).unwrap();
+
+    let body_length = data_frame_header.length() - 10;
+    let body = frame[22..22 + body_length].to_vec();
+
+    match TransportMessage::from_bytes(&mut &frame[..]).unwrap() {
+        TransportMessage::Failure(failure) => {
+            assert_eq!(Some(256), failure.request_id);
+            assert_eq!(body, *failure.body);
+
+            let mut buffer = vec![];
+            failure.to_bytes(&mut buffer).unwrap();
+            assert_eq!(frame, buffer);
+        }
+        message => panic!("Unexpected message {:?}", message),
+    }
+
+    match TransportMessageRef::from_bytes_ref(&mut &frame[..]).unwrap() {
+        TransportMessageRef::Failure(failure) => {
+            assert_eq!(Some(256), failure.request_id);
+            assert_eq!(&body[..], failure.body);
+            // the body is borrowed from the frame
+            assert_eq!(frame[22..].as_ptr(), failure.body.as_ptr());
+        }
+        message => panic!("Unexpected message {:?}", message),
+    }
+}
+
+#[test]
+fn failure_write() {
+    let mut buffer = vec![];
+    TransportMessage::failure(7, Data::from(vec![1, 2, 3])).to_bytes(&mut buffer).unwrap();
+    assert_eq!(32, buffer.len());
+
+    match TransportMessage::from_bytes(&mut &buffer[..]).unwrap() {
+        TransportMessage::Failure(failure) => {
+            assert!(failure.frame_header.is_failed());
+            assert_eq!(Some(7), failure.request_id);
+            assert_eq!(vec![1, 2, 3], *failure.body);
+        }
+        message => panic!("Unexpected message {:?}", message),
+    }
+}
+
+#[test]
+fn frame_too_large() {
+    dump_vec!(dump, "keep-alive.bin");
+    let mut frame = dump;
+    frame[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    let error = TransportMessage::from_bytes(&mut &frame[..]).unwrap_err();
+    assert_eq!(std::io::ErrorKind::InvalidData, error.kind());
+    assert_eq!(
+        Some(&FrameTooLarge {
+            length: u32::MAX as usize,
+            max_length: DEFAULT_MAX_FRAME_LENGTH,
+            skipped: false,
+        }),
+        FrameTooLarge::from_error(&error)
+    );
+
+    let error = TransportMessageRef::from_bytes_ref(&mut &frame[..]).unwrap_err();
+    assert_eq!(
+        "Frame length 4294967295 exceeds maximum frame length 524288",
+        error.to_string()
+    );
+
+    dump_vec!(dump, "keep-alive.bin");
+    let error = TransportMessageRef::from_bytes_ref_limited(&mut &dump[..], 4).unwrap_err();
+    assert_eq!("Frame length 6 exceeds maximum frame length 4", error.to_string());
+    assert!(TransportMessageRef::from_bytes_ref_limited(&mut &dump[..], 6).is_ok());
+}
+
 #[test]
 fn append_request() {
     dump!(reader, "append-request.bin");
@@ -1242,3 +1452,72 @@ fn append_request() {
 
     assert_eq!(data_frame_header.padding(), reader.len());
 }
+
+#[test]
+fn hexdump_annotates_all_dumps() {
+    let dumps: [&[u8]; 4] = [
+        include_bytes!("dumps/create-task-request.bin"),
+        include_bytes!("dumps/error-topic-not-found.bin"),
+        include_bytes!("dumps/keep-alive.bin"),
+        include_bytes!("dumps/append-request.bin"),
+    ];
+
+    for dump in &dumps {
+        let annotations = hexdump::annotate(dump);
+        assert!(annotations.iter().all(|annotation| !annotation.label.starts_with("undecoded")));
+        assert_eq!(dump.len(), annotations.last().unwrap().range.end);
+    }
+
+    dump_vec!(data, "create-task-request.bin");
+    let annotations = hexdump::annotate(&data);
+    let topic_name = annotations.iter().find(|annotation| annotation.label == "ExecuteCommandRequest.topic_name").unwrap();
+    assert_eq!(b"default-topic", &data[topic_name.range.clone()]);
+}
+
+#[test]
+fn append_request_logged_events() {
+    dump!(reader, "append-request.bin");
+
+    let _ = DataFrameHeader::from_bytes(&mut reader).unwrap();
+    let _ = TransportHeader::from_bytes(&mut reader).unwrap();
+    let _ = MessageHeader::from_bytes(&mut reader).unwrap();
+    let request = AppendRequest::from_bytes(&mut reader).unwrap();
+
+    let events: Vec<_> = log::logged_events(&request.data).collect::<Result<_, _>>().unwrap();
+    assert_eq!(1, events.len());
+
+    let event = &events[0];
+    assert_eq!(4_294_967_392, event.position);
+    assert_eq!(4_294_967_392, event.key);
+    assert_eq!(-1, event.producer_id);
+    assert_eq!(-1, event.source_event_partition_id);
+    assert_eq!(-1, event.source_event_position);
+    assert_eq!(
+        BrokerEventMetadata {
+            request_stream_id: None,
+            request_id: None,
+            raft_term: 1,
+            subscriber_key: None,
+            protocol_version: 1,
+            event_type: EventType::RaftEvent,
+            incident_key: None,
+        },
+        event.metadata
+    );
+
+    assert_eq!(
+        log::TypedEvent::Raft(RaftEvent {
+            members: vec![
+                SocketAddress {
+                    host: "localhost".into(),
+                    port: 8001,
+                },
+                SocketAddress {
+                    host: "localhost".into(),
+                    port: 8002,
+                },
+            ],
+        }),
+        event.event().unwrap()
+    );
+}
@@ -0,0 +1,73 @@
+extern crate unterflow_protocol;
+
+use unterflow_protocol::io::FromReader;
+use unterflow_protocol::log::*;
+use unterflow_protocol::message::*;
+use unterflow_protocol::sbe::EventType;
+
+#[test]
+fn read_log_directory() {
+    let directory = LogDirectory::open("tests/log").unwrap();
+
+    let ids: Vec<_> = directory.segments.iter().map(|segment| segment.id).collect();
+    assert_eq!(vec![1, 2, 3], ids);
+    assert_eq!(1024, directory.segments[0].capacity);
+    assert_eq!(SEGMENT_METADATA_LENGTH + 160, directory.segments[0].size());
+
+    let records: Vec<_> = directory.records().collect::<Result<_, _>>().unwrap();
+    assert_eq!(4, records.len());
+
+    assert_eq!(4_294_967_392, records[0].position);
+    assert_eq!(EventType::RaftEvent, records[0].event_type);
+    match records[0].event {
+        TypedEvent::Raft(ref event) => assert_eq!(2, event.members.len()),
+        ref other => panic!("Unexpected event {:?}", other),
+    }
+
+    let tasks: Vec<_> = records[1..3]
+        .iter()
+        .map(|record| match record.event {
+            TypedEvent::Task(ref task) => (record.position, record.key, task.state.as_str(), task.task_type.as_str()),
+            ref other => panic!("Unexpected event {:?}", other),
+        })
+        .collect();
+    assert_eq!(vec![(8_589_934_592, 8_589_934_592, CREATE_STATE, "foo"), (tasks[1].0, 8_589_934_592, CREATED_STATE, "foo")], tasks);
+    assert!(tasks[1].0 > tasks[0].0);
+
+    // the third segment starts with a padding frame
+    assert_eq!(12_884_902_048, records[3].position);
+    assert_eq!(EventType::TaskEvent, records[3].event_type);
+}
+
+#[test]
+fn skip_padding_frames() {
+    let segment = LogSegment::open("tests/log/03.data").unwrap();
+    assert_eq!(3, segment.id);
+    assert_eq!(SEGMENT_METADATA_LENGTH + 32 + 304, segment.size());
+
+    let events: Vec<_> = segment.events().unwrap().collect::<Result<_, _>>().unwrap();
+    assert_eq!(1, events.len());
+    assert_eq!(12_884_902_048, events[0].position);
+
+    // reading a single event skips the padding frame before it as well
+    let buffer = std::fs::read("tests/log/03.data").unwrap();
+    let mut reader = &buffer[SEGMENT_METADATA_LENGTH..segment.size()];
+    let event = LoggedEvent::from_reader(&mut reader).unwrap();
+    assert_eq!(events[0], event);
+
+    let in_memory = LogSegment::from_vec(buffer).unwrap();
+    let in_memory: Vec<_> = in_memory.events().unwrap().collect::<Result<_, _>>().unwrap();
+    assert_eq!(events, in_memory);
+}
+
+#[test]
+fn invalid_segment() {
+    let error = LogSegment::from_vec(vec![0; 64]).unwrap_err();
+    assert_eq!("Segment of 64 bytes is shorter than its metadata", error.to_string());
+
+    let mut buffer = vec![0; 128];
+    buffer[0] = 3;
+    buffer[64] = 200;
+    let error = LogSegment::from_vec(buffer).unwrap_err();
+    assert_eq!("Segment 3 has invalid size 200 for 128 bytes", error.to_string());
+}
@@ -0,0 +1,111 @@
+extern crate unterflow_protocol;
+
+use unterflow_protocol::{frame, sbe};
+use unterflow_protocol::io::FromReader;
+use unterflow_protocol::schema::Schema;
+
+// template id, name, block length and the fields with their block offsets, var length fields
+// have no offset
+type Layout = (u16, &'static str, usize, Vec<(&'static str, Option<usize>)>);
+
+fn layouts(schema: &Schema) -> Vec<Layout> {
+    schema
+        .messages
+        .iter()
+        .map(|message| {
+            let fields = message.composite.fields.iter().map(|field| (field.name, field.offset)).collect();
+            (message.template_id, message.composite.name, message.composite.block_length(), fields)
+        })
+        .collect()
+}
+
+#[test]
+fn client_schema() {
+    let xml = sbe::client_schema().unwrap().to_xml().unwrap();
+    assert_eq!(include_str!("../schema/client-protocol.xml"), xml);
+}
+
+#[test]
+fn raft_schema() {
+    let xml = sbe::raft_schema().unwrap().to_xml().unwrap();
+    assert_eq!(include_str!("../schema/raft-protocol.xml"), xml);
+}
+
+#[test]
+fn gossip_schema() {
+    let xml = sbe::gossip_schema().unwrap().to_xml().unwrap();
+    assert_eq!(include_str!("../schema/gossip-protocol.xml"), xml);
+}
+
+#[test]
+fn transport_schema() {
+    let xml = frame::transport_schema().to_xml().unwrap();
+    assert_eq!(include_str!("../schema/transport-protocol.xml"), xml);
+}
+
+// the layouts of the upstream Zeebe 0.1.0 schemas, so a reordered field fails even if the
+// reference XML was regenerated from it
+#[test]
+fn upstream_message_layouts() {
+    assert_eq!(
+        vec![
+            (0, "ErrorResponse", 1, vec![("error_code", Some(0)), ("error_data", None), ("failed_request", None)]),
+            (10, "ControlMessageRequest", 1, vec![("message_type", Some(0)), ("data", None)]),
+            (11, "ControlMessageResponse", 0, vec![("data", None)]),
+            (20, "ExecuteCommandRequest", 19, vec![("partition_id", Some(0)), ("position", Some(2)), ("key", Some(10)), ("event_type", Some(18)), ("topic_name", None), ("command", None)]),
+            (21, "ExecuteCommandResponse", 18, vec![("partition_id", Some(0)), ("position", Some(2)), ("key", Some(10)), ("topic_name", None), ("event", None)]),
+            (30, "SubscribedEvent", 28, vec![("partition_id", Some(0)), ("position", Some(2)), ("key", Some(10)), ("subscriber_key", Some(18)), ("subscription_type", Some(26)), ("event_type", Some(27)), ("topic_name", None), ("event", None)]),
+            (200, "BrokerEventMetadata", 35, vec![("request_stream_id", Some(0)), ("request_id", Some(4)), ("raft_term", Some(12)), ("subscriber_key", Some(16)), ("protocol_version", Some(24)), ("event_type", Some(26)), ("incident_key", Some(27))]),
+        ],
+        layouts(&sbe::client_schema().unwrap())
+    );
+
+    let member = vec![("partition_id", Some(0)), ("term", Some(2)), ("port", Some(4)), ("topic_name", None), ("host", None)];
+    let membership = vec![("term", Some(0)), ("succeeded", Some(2)), ("configuration_entry_position", Some(3)), ("configuration_entry_term", Some(11)), ("members", None)];
+    let election = vec![("partition_id", Some(0)), ("term", Some(2)), ("last_event_position", Some(4)), ("last_event_term", Some(12)), ("port", Some(16)), ("topic_name", None), ("host", None)];
+    assert_eq!(
+        vec![
+            (0, "JoinRequest", 6, member.clone()),
+            (1, "JoinResponse", 15, membership.clone()),
+            (2, "LeaveRequest", 6, member),
+            (3, "LeaveResponse", 15, membership),
+            (4, "ConfigurationRequest", 16, vec![("partition_id", Some(0)), ("term", Some(2)), ("configuration_entry_position", Some(4)), ("configuration_entry_term", Some(12)), ("members", None), ("topic_name", None)]),
+            (5, "ConfigurationResponse", 3, vec![("term", Some(0)), ("succeeded", Some(2))]),
+            (6, "PollRequest", 18, election.clone()),
+            (7, "PollResponse", 3, vec![("term", Some(0)), ("granted", Some(2))]),
+            (8, "VoteRequest", 18, election),
+            (9, "VoteResponse", 3, vec![("term", Some(0)), ("granted", Some(2))]),
+            (10, "AppendRequest", 26, vec![("partition_id", Some(0)), ("term", Some(2)), ("previous_event_position", Some(4)), ("previous_event_term", Some(12)), ("commit_position", Some(16)), ("port", Some(24)), ("topic_name", None), ("host", None), ("data", None)]),
+            (11, "AppendResponse", 15, vec![("partition_id", Some(0)), ("term", Some(2)), ("succeeded", Some(4)), ("previous_event_position", Some(5)), ("port", Some(13)), ("topic_name", None), ("host", None)]),
+        ],
+        layouts(&sbe::raft_schema().unwrap())
+    );
+
+    assert_eq!(
+        vec![(0, "GossipEvent", 5, vec![("event_type", Some(0)), ("probe_member_port", Some(1)), ("sender_port", Some(3)), ("membership_events", None), ("custom_events", None), ("probe_member_host", None), ("sender_host", None)])],
+        layouts(&sbe::gossip_schema().unwrap())
+    );
+}
+
+// the message headers of frames captured from a broker agree with the reference schemas
+#[test]
+fn captured_message_headers() {
+    // offset of the message header behind the data frame, transport and request response headers
+    let dumps: [(&[u8], usize); 6] = [
+        (include_bytes!("dumps/topology-request.bin"), 22),
+        (include_bytes!("dumps/topology-response.bin"), 22),
+        (include_bytes!("dumps/create-task-request.bin"), 22),
+        (include_bytes!("dumps/create-task-response.bin"), 22),
+        (include_bytes!("dumps/task-subscription-locked-task.bin"), 14),
+        (include_bytes!("dumps/append-request.bin"), 14),
+    ];
+    let schemas = [sbe::client_schema().unwrap(), sbe::raft_schema().unwrap()];
+
+    for &(dump, offset) in &dumps {
+        let header = sbe::MessageHeader::from_reader(&mut &dump[offset..]).unwrap();
+        let schema = schemas.iter().find(|schema| schema.id == header.schema_id).unwrap();
+        let message = schema.messages.iter().find(|message| message.template_id == header.template_id).unwrap();
+        assert_eq!(header.block_length as usize, message.composite.block_length(), "{}", message.composite.name);
+        assert_eq!(header.version, schema.version, "{}", message.composite.name);
+    }
+}
@@ -0,0 +1,78 @@
+extern crate unterflow_protocol;
+
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use unterflow_protocol::{RequestResponseMessage, TransportMessage};
+use unterflow_protocol::io::{Data, FromBytes, ToBytes};
+use unterflow_protocol::message::TopologyRequest;
+use unterflow_protocol::sbe::{ControlMessageRequest, ControlMessageResponse, ControlMessageType};
+use unterflow_protocol::stream::Multiplexer;
+
+const REQUESTS: u64 = 4;
+
+// answers each request on the stream it was received on, the response data is the request id
+fn serve(mut connection: TcpStream) {
+    for _ in 0..2 * REQUESTS {
+        let request = match TransportMessage::from_bytes(&mut connection).unwrap() {
+            TransportMessage::RequestResponse(request) => request,
+            request => panic!("Unexpected request {:?}", request),
+        };
+
+        let request_id = request.request_header.request_id;
+        let response = ControlMessageResponse { data: Data::from(request_id.to_le_bytes().to_vec()) };
+        let mut response = TransportMessage::request(request_id, response);
+        response.frame_header_mut().stream_id = request.frame_header.stream_id;
+        response.to_bytes(&mut connection).unwrap();
+    }
+}
+
+fn request() -> ControlMessageRequest {
+    ControlMessageType::RequestTopology.with(&TopologyRequest {}).unwrap()
+}
+
+#[test]
+fn interleaved_conversations() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = thread::spawn(move || serve(listener.accept().unwrap().0));
+
+    let mut connection = TcpStream::connect(address).unwrap();
+    let multiplexer = Arc::new(Multiplexer::new());
+    let first = multiplexer.open();
+    let second = multiplexer.open();
+
+    let reader = {
+        let multiplexer = multiplexer.clone();
+        let mut connection = connection.try_clone().unwrap();
+        thread::spawn(move || for _ in 0..2 * REQUESTS {
+            multiplexer.route_from(&mut connection).unwrap();
+        })
+    };
+
+    // both conversations use the same request ids, only the stream id tells them apart
+    for idx in 0..REQUESTS {
+        first.request(idx, request()).to_bytes(&mut connection).unwrap();
+        second.request(idx, request()).to_bytes(&mut connection).unwrap();
+    }
+
+    for stream in &[first, second] {
+        for idx in 0..REQUESTS {
+            let response = match stream.recv().unwrap() {
+                TransportMessage::RequestResponse(response) => response,
+                response => panic!("Unexpected response {:?}", response),
+            };
+
+            assert_eq!(stream.id(), response.frame_header.stream_id);
+            assert_eq!(idx, response.request_header.request_id);
+            match response.message {
+                RequestResponseMessage::ControlMessageResponse(ref message) => assert_eq!(idx.to_le_bytes(), message.data[..]),
+                ref message => panic!("Unexpected message {:?}", message),
+            }
+        }
+        assert!(stream.try_recv().is_err());
+    }
+
+    reader.join().unwrap();
+    server.join().unwrap();
+}
@@ -0,0 +1,149 @@
+extern crate unterflow_protocol;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use unterflow_protocol::{SingleRequestMessageRef, TransportMessage, TransportMessageRef};
+use unterflow_protocol::buffer::FrameBuffer;
+use unterflow_protocol::io::{FromBytes, FromBytesRef};
+use unterflow_protocol::sbe::{EventType, SubscribedEvent, SubscriptionType};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(|count| count.get())
+}
+
+const MESSAGES: usize = 10_000;
+
+#[test]
+fn decode_subscribed_events_without_allocation() {
+    let frame = include_bytes!("dumps/task-subscription-locked-task.bin");
+
+    let mut frames = Vec::with_capacity(frame.len() * MESSAGES);
+    for _ in 0..MESSAGES {
+        frames.extend_from_slice(frame);
+    }
+
+    let before = allocations();
+
+    let mut buffer = &frames[..];
+    let mut decoded = 0;
+    while !buffer.is_empty() {
+        let message = TransportMessageRef::from_bytes_ref(&mut buffer).unwrap();
+        if let TransportMessageRef::SingleRequest(ref request) = message {
+            if let SingleRequestMessageRef::SubscribedEvent(ref event) = *request.message() {
+                assert_eq!("default-topic", event.topic_name);
+                assert_eq!(EventType::TaskEvent, event.event_type);
+                assert_eq!(SubscriptionType::TaskSubscription, event.subscription_type);
+                decoded += 1;
+            }
+        }
+    }
+
+    let after = allocations();
+
+    assert_eq!(MESSAGES, decoded);
+    assert_eq!(0, after - before, "expected no allocations while decoding");
+}
+
+#[test]
+fn borrowed_matches_owned() {
+    let frame = include_bytes!("dumps/task-subscription-locked-task.bin");
+
+    let owned = TransportMessage::from_bytes(&mut &frame[..]).unwrap();
+    let borrowed = TransportMessageRef::from_bytes_ref(&mut &frame[..]).unwrap();
+
+    assert_eq!(owned.length(), borrowed.length());
+
+    match (owned, borrowed) {
+        (TransportMessage::SingleRequest(owned), TransportMessageRef::SingleRequest(borrowed)) => {
+            assert_eq!(owned.frame_header, borrowed.frame_header);
+            assert_eq!(owned.message_header, borrowed.message_header);
+
+            match (owned.message, borrowed.message) {
+                (unterflow_protocol::SingleRequestMessage::SubscribedEvent(owned), SingleRequestMessageRef::SubscribedEvent(borrowed)) => {
+                    assert_eq!(&owned.event[..], borrowed.event);
+                    assert_eq!(owned, SubscribedEvent::from(borrowed));
+                }
+                (owned, borrowed) => panic!("Expected subscribed events, got {:?} and {:?}", owned, borrowed),
+            }
+        }
+        (owned, borrowed) => panic!("Expected single requests, got {:?} and {:?}", owned, borrowed),
+    }
+}
+
+#[test]
+fn truncated_frame() {
+    let frame = include_bytes!("dumps/task-subscription-locked-task.bin");
+
+    let mut buffer = &frame[..frame.len() - 8];
+    assert!(TransportMessageRef::from_bytes_ref(&mut buffer).is_err());
+}
+
+#[test]
+fn read_frames_into_reused_buffer_without_allocation() {
+    let frame = include_bytes!("dumps/task-subscription-locked-task.bin");
+
+    let mut frames = Vec::with_capacity(frame.len() * MESSAGES);
+    for _ in 0..MESSAGES {
+        frames.extend_from_slice(frame);
+    }
+
+    let mut reader = &frames[..];
+    let mut buffer = FrameBuffer::new();
+
+    // warm up the buffer with the first frame
+    buffer.read_frame(&mut reader).unwrap();
+
+    let before = allocations();
+
+    let mut decoded = 1;
+    while !reader.is_empty() {
+        let mut frame = buffer.read_frame(&mut reader).unwrap();
+        if let TransportMessageRef::SingleRequest(_) = TransportMessageRef::from_bytes_ref(&mut frame).unwrap() {
+            decoded += 1;
+        }
+    }
+
+    let after = allocations();
+
+    assert_eq!(MESSAGES, decoded);
+    assert_eq!(0, after - before, "expected no allocations while reading frames");
+}
+
+#[test]
+fn read_and_write_with_buffer() {
+    let frame = include_bytes!("dumps/create-deployment-request.bin");
+
+    let mut buffer = FrameBuffer::new();
+
+    let message = TransportMessage::from_reader_with(&mut &frame[..], &mut buffer).unwrap();
+
+    let mut written = vec![];
+    message.to_writer_with(&mut written, &mut buffer).unwrap();
+    assert_eq!(&frame[..], &written[..]);
+
+    let capacity = buffer.capacity();
+    written.clear();
+    message.to_writer_with(&mut written, &mut buffer).unwrap();
+    assert_eq!(capacity, buffer.capacity());
+    assert_eq!(&frame[..], &written[..]);
+}
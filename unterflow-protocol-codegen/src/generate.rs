@@ -0,0 +1,460 @@
+use schema::{invalid, CompositeType, EncodedType, EnumType, Field, Group, Member, MessageDef, Presence, Primitive, Schema,
+             SetType, TypeDef, TypeRef, VarData};
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::io::Error;
+
+const KEYWORDS: &[&str] = &["as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for", "if",
+                            "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static",
+                            "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while", "abstract",
+                            "async", "await", "become", "box", "do", "dyn", "final", "macro", "override", "priv", "try",
+                            "typeof", "unsized", "virtual", "yield"];
+
+// `Default` is only implemented for arrays up to 32 elements
+const MAX_DEFAULT_ARRAY_LENGTH: usize = 32;
+
+pub fn generate(schema: &Schema) -> Result<String, Error> {
+    Generator::new(schema).generate()
+}
+
+struct RustField {
+    sbe: Vec<String>,
+    name: String,
+    ty: String,
+}
+
+impl RustField {
+    fn new(field: &Field, ty: String) -> Self {
+        RustField {
+            sbe: since_version(field.since_version),
+            name: field_name(&field.name),
+            ty,
+        }
+    }
+}
+
+struct Generator<'a> {
+    schema: &'a Schema,
+    // header, group dimension and var data composites are encoded by the library itself
+    builtin: HashSet<&'a str>,
+    out: String,
+}
+
+impl<'a> Generator<'a> {
+    fn new(schema: &'a Schema) -> Self {
+        let mut builtin = HashSet::new();
+        builtin.insert(schema.header_type.as_str());
+        for message in &schema.messages {
+            builtin_types(&message.fields, &mut builtin);
+        }
+
+        Generator {
+            schema,
+            builtin,
+            out: String::new(),
+        }
+    }
+
+    fn generate(mut self) -> Result<String, Error> {
+        self.validate_builtin()?;
+
+        let _ = writeln!(self.out,
+                         "// Generated by unterflow-protocol-codegen from SBE schema {} (id {}, version {}), do not edit.",
+                         self.schema.package.as_deref().unwrap_or("<unnamed>"),
+                         self.schema.id,
+                         self.schema.version);
+        self.out.push('\n');
+        self.out.push_str("#[allow(unused_imports)]\n");
+        self.out.push_str("use unterflow_protocol::io::{CharArray, Data};\n");
+
+        for ty in &self.schema.types {
+            match *ty {
+                TypeDef::Enum(ref ty) => self.enum_type(ty)?,
+                TypeDef::Set(ref ty) => self.set_type(ty)?,
+                TypeDef::Composite(ref ty) if !self.builtin.contains(ty.name.as_str()) => self.composite_type(ty)?,
+                _ => {}
+            }
+        }
+
+        for message in &self.schema.messages {
+            self.message(message)?;
+        }
+
+        Ok(self.out)
+    }
+
+    fn validate_builtin(&self) -> Result<(), Error> {
+        for message in &self.schema.messages {
+            self.validate_members(&message.fields)?;
+        }
+        Ok(())
+    }
+
+    fn validate_members(&self, members: &[Member]) -> Result<(), Error> {
+        for member in members {
+            match *member {
+                Member::Group(ref group) => {
+                    let dimension = self.composite(&group.dimension_type)?;
+                    if self.primitives(dimension) != [Some(Primitive::UInt16), Some(Primitive::UInt8)] {
+                        return Err(invalid(format!("Group dimension {} must encode blockLength as uint16 and numInGroup as uint8",
+                                                   dimension.name)));
+                    }
+                    self.validate_members(&group.fields)?;
+                }
+                Member::Data(ref data) => {
+                    self.var_data_type(data)?;
+                }
+                Member::Field(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn primitives(&self, composite: &CompositeType) -> Vec<Option<Primitive>> {
+        composite
+            .members
+            .iter()
+            .map(|member| match member.ty {
+                     TypeRef::Inline(ref ty) if ty.length == 1 => Some(ty.primitive),
+                     TypeRef::Named(ref name) => {
+                         match self.schema.find_type(name) {
+                             Some(TypeDef::Encoded(ty)) if ty.length == 1 => Some(ty.primitive),
+                             _ => None,
+                         }
+                     }
+                     _ => None,
+                 })
+            .collect()
+    }
+
+    fn composite(&self, name: &str) -> Result<&'a CompositeType, Error> {
+        match self.schema.find_type(name) {
+            Some(TypeDef::Composite(ty)) => Ok(ty),
+            Some(_) => Err(invalid(format!("Type {} is not a composite", name))),
+            None => Err(invalid(format!("Unknown type {}", name))),
+        }
+    }
+
+    fn var_data_type(&self, data: &VarData) -> Result<&'static str, Error> {
+        let composite = self.composite(&data.ty)?;
+
+        if composite.members.len() != 2 || self.primitives(composite)[0] != Some(Primitive::UInt16) {
+            return Err(invalid(format!("Var data {} must encode length as uint16 followed by varData", composite.name)));
+        }
+
+        match composite.members[1].ty {
+            TypeRef::Inline(ref ty) if ty.character_encoding.is_some() => Ok("String"),
+            TypeRef::Inline(_) => Ok("Data"),
+            TypeRef::Named(_) => Err(invalid(format!("Var data {} must declare varData inline", composite.name))),
+        }
+    }
+
+    fn enum_type(&mut self, ty: &EnumType) -> Result<(), Error> {
+        if ty.values.is_empty() {
+            return Err(invalid(format!("Enum {} has no valid values", ty.name)));
+        }
+
+        self.out.push('\n');
+        self.out.push_str("#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]\n");
+        if ty.encoding.rust_type() != "u8" {
+            let _ = writeln!(self.out, "#[enum_type = \"{}\"]", ty.encoding.rust_type());
+        }
+        let _ = writeln!(self.out, "pub enum {} {{", type_name(&ty.name));
+        for (idx, (value, discriminant)) in ty.values.iter().enumerate() {
+            // absent fields of older versions decode to the first value
+            if idx == 0 {
+                self.out.push_str("    #[default]\n");
+            }
+            let _ = writeln!(self.out, "    {} = {},", variant_name(value), discriminant);
+        }
+        self.out.push_str("}\n");
+
+        Ok(())
+    }
+
+    fn set_type(&mut self, ty: &SetType) -> Result<(), Error> {
+        match ty.encoding {
+            Primitive::Int8 | Primitive::Int16 | Primitive::Int32 | Primitive::Int64 => {
+                return Err(invalid(format!("Set {} must use an unsigned encoding type", ty.name)))
+            }
+            _ => {}
+        }
+
+        self.out.push('\n');
+        self.out.push_str("#[derive(Debug, Default, Clone, Copy, PartialEq, SbeFlags)]\n");
+        if ty.encoding.rust_type() != "u8" {
+            let _ = writeln!(self.out, "#[flags_type = \"{}\"]", ty.encoding.rust_type());
+        }
+        let _ = writeln!(self.out, "pub struct {} {{", type_name(&ty.name));
+        for &(ref choice, bit) in &ty.choices {
+            if bit >= ty.encoding.bits() {
+                return Err(invalid(format!("Choice {} of set {} exceeds the {} bits of its encoding",
+                                           choice,
+                                           ty.name,
+                                           ty.encoding.bits())));
+            }
+            let _ = writeln!(self.out, "    #[sbe(bit = \"{}\")]", bit);
+            let _ = writeln!(self.out, "    pub {}: bool,", field_name(choice));
+        }
+        self.out.push_str("}\n");
+
+        Ok(())
+    }
+
+    fn composite_type(&mut self, ty: &CompositeType) -> Result<(), Error> {
+        let mut fields = Vec::new();
+        for member in &ty.members {
+            if let Some(field) = self.field(member)? {
+                fields.push(field);
+            }
+        }
+
+        let defaultable = ty.members.iter().all(|member| self.array_length(&member.ty) <= MAX_DEFAULT_ARRAY_LENGTH);
+
+        self.out.push('\n');
+        if defaultable {
+            self.out.push_str("#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]\n");
+        } else {
+            self.out.push_str("#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]\n");
+        }
+        self.structure(&type_name(&ty.name), &fields);
+
+        Ok(())
+    }
+
+    fn message(&mut self, message: &MessageDef) -> Result<(), Error> {
+        let name = type_name(&message.name);
+        let fields = self.members(&name, &message.fields)?;
+
+        self.out.push('\n');
+        self.out.push_str("#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]\n");
+        let _ = writeln!(self.out,
+                         "#[message(template_id = \"{}\", schema_id = \"{}\", version = \"{}\")]",
+                         message.id,
+                         self.schema.id,
+                         self.schema.version);
+        self.structure(&name, &fields);
+
+        Ok(())
+    }
+
+    fn group(&mut self, parent: &str, group: &Group) -> Result<String, Error> {
+        let name = format!("{}{}", parent, type_name(&group.name));
+        let fields = self.members(&name, &group.fields)?;
+
+        self.out.push('\n');
+        self.out.push_str("#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]\n");
+        self.structure(&name, &fields);
+
+        Ok(name)
+    }
+
+    fn members(&mut self, parent: &str, members: &[Member]) -> Result<Vec<RustField>, Error> {
+        let mut fields = Vec::new();
+        for member in members {
+            match *member {
+                Member::Field(ref field) => {
+                    if let Some(field) = self.field(field)? {
+                        fields.push(field);
+                    }
+                }
+                Member::Group(ref group) => {
+                    let entry = self.group(parent, group)?;
+                    fields.push(RustField {
+                                    sbe: since_version(group.since_version),
+                                    name: field_name(&group.name),
+                                    ty: format!("Vec<{}>", entry),
+                                });
+                }
+                Member::Data(ref data) => {
+                    let ty = self.var_data_type(data)?;
+                    fields.push(RustField {
+                                    sbe: since_version(data.since_version),
+                                    name: field_name(&data.name),
+                                    ty: ty.to_string(),
+                                });
+                }
+            }
+        }
+        Ok(fields)
+    }
+
+    fn field(&self, field: &Field) -> Result<Option<RustField>, Error> {
+        let primitive;
+        let ty = match field.ty {
+            TypeRef::Inline(ref ty) => ty,
+            TypeRef::Named(ref name) => {
+                match self.schema.find_type(name) {
+                    Some(TypeDef::Encoded(ty)) => ty,
+                    Some(other) => {
+                        return match field.presence {
+                                   Presence::Required => Ok(Some(RustField::new(field, type_name(other.name())))),
+                                   Presence::Optional => Err(invalid(format!("Optional field {} must have a primitive type", field.name))),
+                                   Presence::Constant => Ok(None),
+                               }
+                    }
+                    None => {
+                        primitive = EncodedType {
+                            name: name.clone(),
+                            primitive: Primitive::parse(name)
+                                .ok_or_else(|| invalid(format!("Unknown type {} of field {}", name, field.name)))?,
+                            length: 1,
+                            presence: Presence::Required,
+                            null_value: None,
+                            character_encoding: None,
+                        };
+                        &primitive
+                    }
+                }
+            }
+        };
+
+        let presence = if ty.presence == Presence::Required {
+            field.presence
+        } else {
+            ty.presence
+        };
+
+        match presence {
+            Presence::Required => Ok(Some(RustField::new(field, encoded_type(ty)?))),
+            Presence::Optional => {
+                if ty.length != 1 {
+                    return Err(invalid(format!("Optional field {} must not be an array", field.name)));
+                }
+                let mut optional = RustField::new(field, format!("Option<{}>", encoded_type(ty)?));
+                if let Some(null) = field.null_value.as_ref().or(ty.null_value.as_ref()) {
+                    optional.sbe.push(format!("null = \"{}\"", null));
+                }
+                Ok(Some(optional))
+            }
+            Presence::Constant => Ok(None),
+        }
+    }
+
+    fn array_length(&self, ty: &TypeRef) -> usize {
+        match *ty {
+            TypeRef::Inline(ref ty) if ty.primitive != Primitive::Char => ty.length,
+            TypeRef::Named(ref name) => {
+                match self.schema.find_type(name) {
+                    Some(TypeDef::Encoded(ty)) if ty.primitive != Primitive::Char => ty.length,
+                    _ => 1,
+                }
+            }
+            _ => 1,
+        }
+    }
+
+    fn structure(&mut self, name: &str, fields: &[RustField]) {
+        if fields.is_empty() {
+            let _ = writeln!(self.out, "pub struct {} {{}}", name);
+            return;
+        }
+
+        let _ = writeln!(self.out, "pub struct {} {{", name);
+        for field in fields {
+            if !field.sbe.is_empty() {
+                let _ = writeln!(self.out, "    #[sbe({})]", field.sbe.join(", "));
+            }
+            let _ = writeln!(self.out, "    pub {}: {},", field.name, field.ty);
+        }
+        self.out.push_str("}\n");
+    }
+}
+
+fn builtin_types<'a>(members: &'a [Member], builtin: &mut HashSet<&'a str>) {
+    for member in members {
+        match *member {
+            Member::Group(ref group) => {
+                builtin.insert(group.dimension_type.as_str());
+                builtin_types(&group.fields, builtin);
+            }
+            Member::Data(ref data) => {
+                builtin.insert(data.ty.as_str());
+            }
+            Member::Field(_) => {}
+        }
+    }
+}
+
+fn encoded_type(ty: &EncodedType) -> Result<String, Error> {
+    match (ty.primitive, ty.length) {
+        (_, 0) => Err(invalid(format!("Type {} has zero length outside of var data", ty.name))),
+        (primitive, 1) => Ok(primitive.rust_type().to_string()),
+        (Primitive::Char, length) => Ok(format!("CharArray<{}>", length)),
+        (primitive, length) => Ok(format!("[{}; {}]", primitive.rust_type(), length)),
+    }
+}
+
+fn since_version(since_version: Option<u16>) -> Vec<String> {
+    since_version
+        .into_iter()
+        .map(|version| format!("since_version = \"{}\"", version))
+        .collect()
+}
+
+pub fn field_name(name: &str) -> String {
+    let snake = snake_case(name);
+    if KEYWORDS.contains(&snake.as_str()) {
+        format!("r#{}", snake)
+    } else {
+        snake
+    }
+}
+
+pub fn type_name(name: &str) -> String {
+    if name.contains('_') || !name.chars().any(char::is_lowercase) {
+        name.split('_').map(capitalize).collect()
+    } else {
+        let mut chars = name.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    }
+}
+
+fn variant_name(name: &str) -> String {
+    type_name(name)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect())
+        .unwrap_or_default()
+}
+
+pub fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len() + 4);
+
+    for (idx, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && idx > 0 {
+            let previous = chars[idx - 1];
+            let next_lower = chars.get(idx + 1).is_some_and(|c| c.is_lowercase());
+            if previous.is_lowercase() || previous.is_ascii_digit() || (previous.is_uppercase() && next_lower) {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+
+    snake
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert_eq!("partition_id", field_name("partitionId"));
+        assert_eq!("request_id", field_name("requestID"));
+        assert_eq!("r#type", field_name("type"));
+        assert_eq!("batch_begin", field_name("BATCH_BEGIN"));
+        assert_eq!("ExecuteCommandRequest", type_name("ExecuteCommandRequest"));
+        assert_eq!("DataFrameHeader", type_name("dataFrameHeader"));
+        assert_eq!("TaskEvent", type_name("TASK_EVENT"));
+    }
+}
@@ -0,0 +1,46 @@
+//! Generates Rust message types from an SBE XML schema.
+//!
+//! The generated code uses the `unterflow-protocol` derives, so the including crate needs
+//! `#[macro_use] extern crate unterflow_protocol_derive;`. A typical `build.rs`:
+//!
+//! ```no_run
+//! extern crate unterflow_protocol_codegen;
+//!
+//! use std::env;
+//! use std::path::Path;
+//!
+//! fn main() {
+//!     let out = Path::new(&env::var("OUT_DIR").unwrap()).join("protocol.rs");
+//!     println!("cargo:rerun-if-changed=schema/protocol.xml");
+//!     unterflow_protocol_codegen::generate_file("schema/protocol.xml", out).unwrap();
+//! }
+//! ```
+//!
+//! and the generated file is included with `include!(concat!(env!("OUT_DIR"), "/protocol.rs"));`.
+
+extern crate roxmltree;
+
+mod generate;
+pub mod schema;
+
+pub use schema::Schema;
+use std::fs;
+use std::io::Error;
+use std::path::Path;
+
+pub fn generate(xml: &str) -> Result<String, Error> {
+    let schema = Schema::parse(xml)?;
+    generate::generate(&schema)
+}
+
+pub fn generate_file<P: AsRef<Path>, Q: AsRef<Path>>(schema: P, output: Q) -> Result<(), Error> {
+    let xml = fs::read_to_string(schema)?;
+    let code = generate(&xml)?;
+
+    // keep the file untouched if nothing changed to not trigger a rebuild
+    if fs::read_to_string(output.as_ref()).ok().as_ref() == Some(&code) {
+        return Ok(());
+    }
+
+    fs::write(output, code)
+}
@@ -0,0 +1,28 @@
+extern crate unterflow_protocol_codegen;
+
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result = match args.len() {
+        1 => {
+            fs::read_to_string(&args[0])
+                .and_then(|xml| unterflow_protocol_codegen::generate(&xml))
+                .and_then(|code| io::stdout().write_all(code.as_bytes()))
+        }
+        2 => unterflow_protocol_codegen::generate_file(&args[0], &args[1]),
+        _ => {
+            eprintln!("Usage: unterflow-protocol-codegen <schema.xml> [output.rs]");
+            process::exit(2);
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("Unable to generate code: {}", e);
+        process::exit(1);
+    }
+}
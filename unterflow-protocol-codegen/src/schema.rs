@@ -0,0 +1,407 @@
+use roxmltree::{Document, Node};
+use std::io::{Error, ErrorKind};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Primitive {
+    Char,
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Int64,
+    UInt64,
+}
+
+impl Primitive {
+    pub fn parse(name: &str) -> Option<Primitive> {
+        match name {
+            "char" => Some(Primitive::Char),
+            "int8" => Some(Primitive::Int8),
+            "uint8" => Some(Primitive::UInt8),
+            "int16" => Some(Primitive::Int16),
+            "uint16" => Some(Primitive::UInt16),
+            "int32" => Some(Primitive::Int32),
+            "uint32" => Some(Primitive::UInt32),
+            "int64" => Some(Primitive::Int64),
+            "uint64" => Some(Primitive::UInt64),
+            _ => None,
+        }
+    }
+
+    pub fn rust_type(&self) -> &'static str {
+        match *self {
+            Primitive::Char | Primitive::UInt8 => "u8",
+            Primitive::Int8 => "i8",
+            Primitive::Int16 => "i16",
+            Primitive::UInt16 => "u16",
+            Primitive::Int32 => "i32",
+            Primitive::UInt32 => "u32",
+            Primitive::Int64 => "i64",
+            Primitive::UInt64 => "u64",
+        }
+    }
+
+    pub fn bits(&self) -> u32 {
+        match *self {
+            Primitive::Char | Primitive::Int8 | Primitive::UInt8 => 8,
+            Primitive::Int16 | Primitive::UInt16 => 16,
+            Primitive::Int32 | Primitive::UInt32 => 32,
+            Primitive::Int64 | Primitive::UInt64 => 64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Presence {
+    Required,
+    Optional,
+    Constant,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodedType {
+    pub name: String,
+    pub primitive: Primitive,
+    pub length: usize,
+    pub presence: Presence,
+    pub null_value: Option<String>,
+    pub character_encoding: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumType {
+    pub name: String,
+    pub encoding: Primitive,
+    pub values: Vec<(String, u64)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetType {
+    pub name: String,
+    pub encoding: Primitive,
+    pub choices: Vec<(String, u32)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositeType {
+    pub name: String,
+    pub members: Vec<Field>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeDef {
+    Encoded(EncodedType),
+    Enum(EnumType),
+    Set(SetType),
+    Composite(CompositeType),
+}
+
+impl TypeDef {
+    pub fn name(&self) -> &str {
+        match *self {
+            TypeDef::Encoded(ref ty) => &ty.name,
+            TypeDef::Enum(ref ty) => &ty.name,
+            TypeDef::Set(ref ty) => &ty.name,
+            TypeDef::Composite(ref ty) => &ty.name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeRef {
+    Named(String),
+    Inline(EncodedType),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub ty: TypeRef,
+    pub presence: Presence,
+    pub null_value: Option<String>,
+    pub since_version: Option<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    pub name: String,
+    pub dimension_type: String,
+    pub since_version: Option<u16>,
+    pub fields: Vec<Member>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VarData {
+    pub name: String,
+    pub ty: String,
+    pub since_version: Option<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Member {
+    Field(Field),
+    Group(Group),
+    Data(VarData),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageDef {
+    pub name: String,
+    pub id: u16,
+    pub fields: Vec<Member>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+    pub package: Option<String>,
+    pub id: u16,
+    pub version: u16,
+    pub header_type: String,
+    pub types: Vec<TypeDef>,
+    pub messages: Vec<MessageDef>,
+}
+
+impl Schema {
+    pub fn parse(xml: &str) -> Result<Schema, Error> {
+        let document = Document::parse(xml).map_err(|e| invalid(format!("Unable to parse schema: {}", e)))?;
+        let root = document.root_element();
+
+        if root.tag_name().name() != "messageSchema" {
+            return Err(invalid(format!("Expected messageSchema but found {}", root.tag_name().name())));
+        }
+
+        let mut types = Vec::new();
+        for node in elements(root).filter(|node| node.tag_name().name() == "types") {
+            for node in elements(node) {
+                parse_type(node, &mut types)?;
+            }
+        }
+
+        let messages = elements(root)
+            .filter(|node| node.tag_name().name() == "message")
+            .map(parse_message)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Schema {
+               package: root.attribute("package").map(String::from),
+               id: parse_attribute(root, "id")?,
+               version: optional_attribute(root, "version")?.unwrap_or(0),
+               header_type: root.attribute("headerType").unwrap_or("messageHeader").to_string(),
+               types,
+               messages,
+           })
+    }
+
+    pub fn find_type(&self, name: &str) -> Option<&TypeDef> {
+        self.types.iter().find(|ty| ty.name() == name)
+    }
+}
+
+fn parse_type(node: Node, types: &mut Vec<TypeDef>) -> Result<String, Error> {
+    let ty = match node.tag_name().name() {
+        "type" => TypeDef::Encoded(parse_encoded_type(node)?),
+        "enum" => {
+            let encoding = encoding_type(node, types)?;
+            let values = elements(node)
+                .filter(|node| node.tag_name().name() == "validValue")
+                .map(|value| {
+                         let text = value.text().unwrap_or("").trim();
+                         let number = if encoding == Primitive::Char && text.len() == 1 {
+                             u64::from(text.as_bytes()[0])
+                         } else {
+                             text.parse()
+                                 .map_err(|_| invalid(format!("Invalid value {:?} of enum {}", text, required_attribute(node, "name").unwrap_or(""))))?
+                         };
+                         Ok((required_attribute(value, "name")?.to_string(), number))
+                     })
+                .collect::<Result<_, Error>>()?;
+            TypeDef::Enum(EnumType {
+                              name: required_attribute(node, "name")?.to_string(),
+                              encoding,
+                              values,
+                          })
+        }
+        "set" => {
+            let encoding = encoding_type(node, types)?;
+            let choices = elements(node)
+                .filter(|node| node.tag_name().name() == "choice")
+                .map(|choice| {
+                         let text = choice.text().unwrap_or("").trim();
+                         let bit = text.parse()
+                             .map_err(|_| invalid(format!("Invalid bit {:?} of set {}", text, required_attribute(node, "name").unwrap_or(""))))?;
+                         Ok((required_attribute(choice, "name")?.to_string(), bit))
+                     })
+                .collect::<Result<_, Error>>()?;
+            TypeDef::Set(SetType {
+                             name: required_attribute(node, "name")?.to_string(),
+                             encoding,
+                             choices,
+                         })
+        }
+        "composite" => {
+            let mut members = Vec::new();
+            for member in elements(node) {
+                let name = required_attribute(member, "name")?.to_string();
+                let since_version = optional_attribute(member, "sinceVersion")?;
+                let field = match member.tag_name().name() {
+                    "type" => {
+                        let ty = parse_encoded_type(member)?;
+                        Field {
+                            name,
+                            presence: ty.presence,
+                            null_value: None,
+                            ty: TypeRef::Inline(ty),
+                            since_version,
+                        }
+                    }
+                    "ref" => {
+                        Field {
+                            name,
+                            ty: TypeRef::Named(required_attribute(member, "type")?.to_string()),
+                            presence: Presence::Required,
+                            null_value: None,
+                            since_version,
+                        }
+                    }
+                    // nested enums, sets and composites are hoisted to the schema types
+                    _ => {
+                        let ty = parse_type(member, types)?;
+                        Field {
+                            name,
+                            ty: TypeRef::Named(ty),
+                            presence: Presence::Required,
+                            null_value: None,
+                            since_version,
+                        }
+                    }
+                };
+                members.push(field);
+            }
+            TypeDef::Composite(CompositeType {
+                                   name: required_attribute(node, "name")?.to_string(),
+                                   members,
+                               })
+        }
+        other => return Err(invalid(format!("Unsupported type element {}", other))),
+    };
+
+    let name = ty.name().to_string();
+    types.push(ty);
+    Ok(name)
+}
+
+fn parse_encoded_type(node: Node) -> Result<EncodedType, Error> {
+    let name = required_attribute(node, "name")?;
+    let primitive = required_attribute(node, "primitiveType")?;
+
+    Ok(EncodedType {
+           name: name.to_string(),
+           primitive: Primitive::parse(primitive)
+               .ok_or_else(|| invalid(format!("Unsupported primitive type {} of {}", primitive, name)))?,
+           length: optional_attribute(node, "length")?.unwrap_or(1),
+           presence: presence(node)?,
+           null_value: node.attribute("nullValue").map(String::from),
+           character_encoding: node.attribute("characterEncoding").map(String::from),
+       })
+}
+
+fn encoding_type(node: Node, types: &[TypeDef]) -> Result<Primitive, Error> {
+    let encoding = required_attribute(node, "encodingType")?;
+
+    Primitive::parse(encoding)
+        .or_else(|| {
+                     types
+                         .iter()
+                         .filter_map(|ty| match *ty {
+                                         TypeDef::Encoded(ref ty) if ty.name == encoding => Some(ty.primitive),
+                                         _ => None,
+                                     })
+                         .next()
+                 })
+        .ok_or_else(|| invalid(format!("Unknown encoding type {} of {}", encoding, required_attribute(node, "name").unwrap_or(""))))
+}
+
+fn parse_message(node: Node) -> Result<MessageDef, Error> {
+    Ok(MessageDef {
+           name: required_attribute(node, "name")?.to_string(),
+           id: parse_attribute(node, "id")?,
+           fields: parse_members(node)?,
+       })
+}
+
+fn parse_members(node: Node) -> Result<Vec<Member>, Error> {
+    elements(node)
+        .map(|member| {
+            let name = required_attribute(member, "name")?.to_string();
+            let since_version = optional_attribute(member, "sinceVersion")?;
+
+            match member.tag_name().name() {
+                "field" => {
+                    Ok(Member::Field(Field {
+                                         name,
+                                         ty: TypeRef::Named(required_attribute(member, "type")?.to_string()),
+                                         presence: presence(member)?,
+                                         null_value: member.attribute("nullValue").map(String::from),
+                                         since_version,
+                                     }))
+                }
+                "group" => {
+                    Ok(Member::Group(Group {
+                                         name,
+                                         dimension_type: member.attribute("dimensionType").unwrap_or("groupSizeEncoding").to_string(),
+                                         since_version,
+                                         fields: parse_members(member)?,
+                                     }))
+                }
+                "data" => {
+                    Ok(Member::Data(VarData {
+                                        name,
+                                        ty: required_attribute(member, "type")?.to_string(),
+                                        since_version,
+                                    }))
+                }
+                other => Err(invalid(format!("Unsupported message element {}", other))),
+            }
+        })
+        .collect()
+}
+
+fn presence(node: Node) -> Result<Presence, Error> {
+    match node.attribute("presence") {
+        None | Some("required") => Ok(Presence::Required),
+        Some("optional") => Ok(Presence::Optional),
+        Some("constant") => Ok(Presence::Constant),
+        Some(other) => Err(invalid(format!("Unknown presence {} of {}", other, node.attribute("name").unwrap_or("")))),
+    }
+}
+
+fn elements<'a, 'input>(node: Node<'a, 'input>) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(|node| node.is_element())
+}
+
+fn required_attribute<'a>(node: Node<'a, '_>, name: &str) -> Result<&'a str, Error> {
+    node.attribute(name)
+        .ok_or_else(|| invalid(format!("Missing attribute {} of {}", name, node.tag_name().name())))
+}
+
+fn parse_attribute<T: ::std::str::FromStr>(node: Node, name: &str) -> Result<T, Error> {
+    optional_attribute(node, name)?.ok_or_else(|| invalid(format!("Missing attribute {} of {}", name, node.tag_name().name())))
+}
+
+fn optional_attribute<T: ::std::str::FromStr>(node: Node, name: &str) -> Result<Option<T>, Error> {
+    match node.attribute(name) {
+        Some(value) => {
+            value
+                .parse()
+                .map(Some)
+                .map_err(|_| invalid(format!("Invalid attribute {}={:?} of {}", name, value, node.tag_name().name())))
+        }
+        None => Ok(None),
+    }
+}
+
+pub fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,277 @@
+extern crate unterflow_protocol;
+extern crate unterflow_protocol_codegen;
+#[macro_use]
+extern crate unterflow_protocol_derive;
+
+// variant names follow the schema
+#[allow(clippy::enum_variant_names)]
+mod client_protocol {
+    include!("generated/client_protocol.rs");
+}
+
+// only some of the raft messages are transcoded
+#[allow(dead_code)]
+mod raft_protocol {
+    include!("generated/raft_protocol.rs");
+}
+
+mod gossip_protocol {
+    include!("generated/gossip_protocol.rs");
+}
+
+mod transport_protocol {
+    include!("generated/transport_protocol.rs");
+}
+
+mod features {
+    include!("generated/features.rs");
+}
+
+use std::fs;
+use unterflow_protocol::frame;
+use unterflow_protocol::io::{CharArray, ConstBlockLength, Data, FromReader, FromReaderVersion, HasBlockLength, HasMessageLength, ToWriter, ToWriterVersion};
+use unterflow_protocol::sbe::{self, ConstMessageHeader};
+
+fn transcode<A: ToWriter, B: FromReader + ToWriter>(value: &A) -> B {
+    let mut bytes = vec![];
+    value.to_writer(&mut bytes).unwrap();
+
+    let decoded = B::from_reader(&mut &bytes[..]).unwrap();
+
+    let mut encoded = vec![];
+    decoded.to_writer(&mut encoded).unwrap();
+    assert_eq!(bytes, encoded);
+
+    decoded
+}
+
+#[test]
+fn generated_code_is_up_to_date() {
+    let schemas = [("../schema/client-protocol.xml", "tests/generated/client_protocol.rs"),
+                   ("../schema/raft-protocol.xml", "tests/generated/raft_protocol.rs"),
+                   ("../schema/gossip-protocol.xml", "tests/generated/gossip_protocol.rs"),
+                   ("../schema/transport-protocol.xml", "tests/generated/transport_protocol.rs"),
+                   ("tests/schema/features.xml", "tests/generated/features.rs")];
+
+    for &(schema, generated) in &schemas {
+        let xml = fs::read_to_string(schema).unwrap();
+        let expected = fs::read_to_string(generated).unwrap();
+
+        assert!(unterflow_protocol_codegen::generate(&xml).unwrap() == expected,
+                "{} is outdated, regenerate with `cargo run -- {} {}`",
+                generated,
+                schema,
+                generated);
+    }
+}
+
+#[test]
+fn client_protocol() {
+    assert_eq!(sbe::ErrorResponse::HEADER, client_protocol::ErrorResponse::HEADER);
+    assert_eq!(sbe::ControlMessageRequest::HEADER, client_protocol::ControlMessageRequest::HEADER);
+    assert_eq!(sbe::ControlMessageResponse::HEADER, client_protocol::ControlMessageResponse::HEADER);
+    assert_eq!(sbe::ExecuteCommandRequest::HEADER, client_protocol::ExecuteCommandRequest::HEADER);
+    assert_eq!(sbe::ExecuteCommandResponse::HEADER, client_protocol::ExecuteCommandResponse::HEADER);
+    assert_eq!(sbe::SubscribedEvent::HEADER, client_protocol::SubscribedEvent::HEADER);
+    assert_eq!(sbe::BrokerEventMetadata::HEADER, client_protocol::BrokerEventMetadata::HEADER);
+
+    let request = sbe::ExecuteCommandRequest {
+        partition_id: 1,
+        position: 2,
+        key: 3,
+        event_type: sbe::EventType::WorkflowInstanceEvent,
+        topic_name: "default-topic".to_string(),
+        command: Data::from(vec![1, 2, 3]),
+    };
+
+    let generated: client_protocol::ExecuteCommandRequest = transcode(&request);
+    assert_eq!(client_protocol::EventType::WorkflowInstanceEvent, generated.event_type);
+    assert_eq!(request.topic_name, generated.topic_name);
+    assert_eq!(request.message_length(), generated.message_length());
+
+    let event = sbe::SubscribedEvent {
+        partition_id: 1,
+        position: 2,
+        key: 3,
+        subscriber_key: 4,
+        subscription_type: sbe::SubscriptionType::TopicSubscription,
+        event_type: sbe::EventType::IncidentEvent,
+        topic_name: "default-topic".to_string(),
+        event: Data::from(vec![4, 5, 6]),
+    };
+
+    let generated: client_protocol::SubscribedEvent = transcode(&event);
+    assert_eq!(client_protocol::SubscriptionType::TopicSubscription, generated.subscription_type);
+    assert_eq!(*event.event, *generated.event);
+}
+
+#[test]
+fn raft_protocol() {
+    assert_eq!(sbe::AppendRequest::HEADER, raft_protocol::AppendRequest::HEADER);
+
+    let request = sbe::AppendRequest {
+        partition_id: 1,
+        term: 2,
+        previous_event_position: 3,
+        previous_event_term: -1,
+        commit_position: 4,
+        port: 51017,
+        topic_name: "internal-system".to_string(),
+        host: "localhost".to_string(),
+        data: Data::from(vec![7, 8, 9]),
+    };
+
+    let generated: raft_protocol::AppendRequest = transcode(&request);
+    assert_eq!(request.previous_event_term, generated.previous_event_term);
+    assert_eq!(request.host, generated.host);
+
+    assert_eq!(sbe::JoinResponse::HEADER, raft_protocol::JoinResponse::HEADER);
+    assert_eq!(sbe::VoteRequest::HEADER, raft_protocol::VoteRequest::HEADER);
+    assert_eq!(sbe::AppendResponse::HEADER, raft_protocol::AppendResponse::HEADER);
+
+    let response = sbe::JoinResponse {
+        term: 2,
+        succeeded: sbe::BooleanType::True,
+        configuration_entry_position: 3,
+        configuration_entry_term: 1,
+        members: vec![sbe::RaftMember {
+                          port: 51017,
+                          host: "localhost".to_string(),
+                      }],
+    };
+
+    let generated: raft_protocol::JoinResponse = transcode(&response);
+    assert_eq!(raft_protocol::BooleanType::True, generated.succeeded);
+    assert_eq!(response.members[0].host, generated.members[0].host);
+}
+
+#[test]
+fn gossip_protocol() {
+    assert_eq!(sbe::GossipEvent::HEADER, gossip_protocol::GossipEvent::HEADER);
+
+    let event = sbe::GossipEvent {
+        event_type: sbe::GossipEventType::Ack,
+        probe_member_port: 51016,
+        sender_port: 51017,
+        membership_events: vec![sbe::MembershipEvent {
+                                    event_type: sbe::MembershipEventType::Alive,
+                                    gossip_epoch: 1,
+                                    gossip_heartbeat: 2,
+                                    port: 51016,
+                                    host: "localhost".to_string(),
+                                }],
+        custom_events: vec![],
+        probe_member_host: "localhost".to_string(),
+        sender_host: "localhost".to_string(),
+    };
+
+    let generated: gossip_protocol::GossipEvent = transcode(&event);
+    assert_eq!(gossip_protocol::GossipEventType::Ack, generated.event_type);
+    assert_eq!(gossip_protocol::MembershipEventType::Alive, generated.membership_events[0].event_type);
+}
+
+#[test]
+fn transport_protocol() {
+    assert_eq!(frame::DataFrameHeader::block_length(), transport_protocol::DataFrameHeader::block_length());
+    assert_eq!(frame::TransportHeader::block_length(), transport_protocol::TransportHeader::block_length());
+    assert_eq!(frame::RequestResponseHeader::block_length(), transport_protocol::RequestResponseHeader::block_length());
+    assert_eq!(frame::ControlMessage::block_length(), transport_protocol::ControlMessage::block_length());
+
+    let mut header = frame::DataFrameHeader {
+        length: 42,
+        version: 0,
+        flags: frame::FrameFlags::default(),
+        frame_type: frame::DataFrameType::Padding,
+        stream_id: 7,
+    };
+    header.set_batch_end(true);
+    header.set_failed(true);
+
+    let generated: transport_protocol::DataFrameHeader = transcode(&header);
+    assert!(!generated.flags.batch_begin && generated.flags.batch_end && generated.flags.failed);
+    assert_eq!(transport_protocol::DataFrameType::Padding, generated.frame_type);
+
+    let generated: transport_protocol::TransportHeader = transcode(&frame::TransportHeader { protocol: frame::TransportProtocol::ControlMessage });
+    assert_eq!(transport_protocol::TransportProtocol::ControlMessage, generated.protocol);
+}
+
+#[test]
+fn features() {
+    use features::*;
+
+    let message = Features {
+        symbol: CharArray::new("ZEEBE").unwrap(),
+        ports: [51015, 51016, 51017],
+        timeout: None,
+        retries: Some(-3),
+        capabilities: Capabilities {
+            compression: false,
+            batching: true,
+        },
+        endpoint: Endpoint {
+            port: 26500,
+            transport: Transport::Udp,
+        },
+        priority: Some(5),
+        partitions: vec![FeaturesPartitions {
+                             partition_id: 1,
+                             replicas: vec![FeaturesPartitionsReplicas { node_id: 0 }, FeaturesPartitionsReplicas { node_id: 2 }],
+                         }],
+        name: "features".to_string(),
+        payload: Data::from(vec![1, 2]),
+    };
+
+    assert_eq!(6 + 6 + 4 + 1 + 2 + 3 + 1, Features::BLOCK_LENGTH);
+    assert_eq!(6 + 6 + 4 + 1 + 2 + 3, Features::block_length_version(1));
+
+    let mut bytes = vec![];
+    message.to_writer(&mut bytes).unwrap();
+    assert_eq!(message.message_length() as usize, bytes.len());
+    // the constant version field isn't encoded, the null timeout is written as 0
+    assert_eq!(&[0, 0, 0, 0, 253], &bytes[12..17]);
+    assert_eq!(b'U', bytes[21]);
+    assert_eq!(message, Features::from_reader(&mut &bytes[..]).unwrap());
+
+    let mut bytes = vec![];
+    message.to_writer_version(&mut bytes, 1).unwrap();
+    let decoded = Features::from_reader_version(&mut &bytes[..], 1).unwrap();
+    assert_eq!(None, decoded.priority);
+    assert!(decoded.payload.is_empty());
+    assert_eq!(message.partitions, decoded.partitions);
+
+    let reference = FeaturesRef::from_bytes_ref_version(&mut &bytes[..], 1).unwrap();
+    assert_eq!("features", reference.name);
+}
+
+#[test]
+fn invalid_schemas() {
+    let schema = |types: &str, message: &str| {
+        format!(r#"<sbe:messageSchema xmlns:sbe="http://fixprotocol.io/2016/sbe" id="1" version="1">
+                       <types>{}</types>
+                       <sbe:message name="Test" id="1">{}</sbe:message>
+                   </sbe:messageSchema>"#,
+                types,
+                message)
+    };
+
+    let error = |types: &str, message: &str| unterflow_protocol_codegen::generate(&schema(types, message)).unwrap_err().to_string();
+
+    assert_eq!("Unknown type Missing of field value", error("", r#"<field name="value" id="1" type="Missing"/>"#));
+    assert_eq!("Unsupported primitive type float of Price",
+               error(r#"<type name="Price" primitiveType="float"/>"#, ""));
+    assert_eq!("Group dimension groupSizeEncoding must encode blockLength as uint16 and numInGroup as uint8",
+               error(r#"<composite name="groupSizeEncoding">
+                            <type name="blockLength" primitiveType="uint16"/>
+                            <type name="numInGroup" primitiveType="uint16"/>
+                        </composite>"#,
+                     r#"<group name="entries" id="1"><field name="value" id="2" type="uint8"/></group>"#));
+    assert_eq!("Var data varDataEncoding must encode length as uint16 followed by varData",
+               error(r#"<composite name="varDataEncoding">
+                            <type name="length" primitiveType="uint32"/>
+                            <type name="varData" primitiveType="uint8" length="0"/>
+                        </composite>"#,
+                     r#"<data name="value" id="1" type="varDataEncoding"/>"#));
+    assert_eq!("Choice overflow of set Flags exceeds the 8 bits of its encoding",
+               error(r#"<set name="Flags" encodingType="uint8"><choice name="overflow">8</choice></set>"#, ""));
+    assert!(error("", "<field").starts_with("Unable to parse schema"));
+}
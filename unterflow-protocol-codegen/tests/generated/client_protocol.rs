@@ -0,0 +1,117 @@
+// Generated by unterflow-protocol-codegen from SBE schema io.zeebe.protocol.clientapi (id 0, version 1), do not edit.
+
+#[allow(unused_imports)]
+use unterflow_protocol::io::{CharArray, Data};
+
+#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub enum ErrorCode {
+    #[default]
+    MessageNotSupported = 0,
+    TopicNotFound = 1,
+    RequestWriteFailure = 2,
+    InvalidClientVersion = 3,
+    RequestTimeout = 4,
+    RequestProcessingFailure = 5,
+    InvalidMessage = 6,
+}
+
+#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub enum ControlMessageType {
+    #[default]
+    AddTaskSubscription = 0,
+    RemoveTaskSubscription = 1,
+    IncreaseTaskSubscriptionCredits = 2,
+    RemoveTopicSubscription = 3,
+    RequestTopology = 4,
+    RequestPartitions = 5,
+}
+
+#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub enum EventType {
+    #[default]
+    TaskEvent = 0,
+    RaftEvent = 1,
+    SubscriptionEvent = 2,
+    SubscriberEvent = 3,
+    DeploymentEvent = 4,
+    WorkflowInstanceEvent = 5,
+    IncidentEvent = 6,
+    WorkflowEvent = 7,
+    NoopEvent = 8,
+    TopicEvent = 9,
+    PartitionEvent = 10,
+}
+
+#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub enum SubscriptionType {
+    #[default]
+    TaskSubscription = 0,
+    TopicSubscription = 1,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "0", schema_id = "0", version = "1")]
+pub struct ErrorResponse {
+    pub error_code: ErrorCode,
+    pub error_data: Data,
+    pub failed_request: Data,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "10", schema_id = "0", version = "1")]
+pub struct ControlMessageRequest {
+    pub message_type: ControlMessageType,
+    pub data: Data,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "11", schema_id = "0", version = "1")]
+pub struct ControlMessageResponse {
+    pub data: Data,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "20", schema_id = "0", version = "1")]
+pub struct ExecuteCommandRequest {
+    pub partition_id: u16,
+    pub position: u64,
+    pub key: u64,
+    pub event_type: EventType,
+    pub topic_name: String,
+    pub command: Data,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "21", schema_id = "0", version = "1")]
+pub struct ExecuteCommandResponse {
+    pub partition_id: u16,
+    pub position: u64,
+    pub key: u64,
+    pub topic_name: String,
+    pub event: Data,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "30", schema_id = "0", version = "1")]
+pub struct SubscribedEvent {
+    pub partition_id: u16,
+    pub position: u64,
+    pub key: u64,
+    pub subscriber_key: u64,
+    pub subscription_type: SubscriptionType,
+    pub event_type: EventType,
+    pub topic_name: String,
+    pub event: Data,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "200", schema_id = "0", version = "1")]
+pub struct BrokerEventMetadata {
+    pub request_stream_id: Option<i32>,
+    pub request_id: Option<u64>,
+    pub raft_term: i32,
+    pub subscriber_key: Option<u64>,
+    pub protocol_version: u16,
+    pub event_type: EventType,
+    pub incident_key: Option<u64>,
+}
@@ -0,0 +1,55 @@
+// Generated by unterflow-protocol-codegen from SBE schema unterflow.codegen.test (id 42, version 2), do not edit.
+
+#[allow(unused_imports)]
+use unterflow_protocol::io::{CharArray, Data};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, SbeFlags)]
+#[flags_type = "u16"]
+pub struct Capabilities {
+    #[sbe(bit = "0")]
+    pub compression: bool,
+    #[sbe(bit = "9")]
+    pub batching: bool,
+}
+
+#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub enum Transport {
+    #[default]
+    Tcp = 84,
+    Udp = 85,
+}
+
+#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub struct Endpoint {
+    pub port: u16,
+    pub transport: Transport,
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub struct FeaturesPartitionsReplicas {
+    pub node_id: i32,
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub struct FeaturesPartitions {
+    pub partition_id: u16,
+    pub replicas: Vec<FeaturesPartitionsReplicas>,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "1", schema_id = "42", version = "2")]
+pub struct Features {
+    pub symbol: CharArray<6>,
+    pub ports: [u16; 3],
+    #[sbe(null = "0")]
+    pub timeout: Option<u32>,
+    pub retries: Option<i8>,
+    pub capabilities: Capabilities,
+    pub endpoint: Endpoint,
+    #[sbe(since_version = "2", null = "0")]
+    pub priority: Option<u8>,
+    pub partitions: Vec<FeaturesPartitions>,
+    pub name: String,
+    #[sbe(since_version = "2")]
+    pub payload: Data,
+}
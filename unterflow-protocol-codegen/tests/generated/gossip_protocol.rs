@@ -0,0 +1,55 @@
+// Generated by unterflow-protocol-codegen from SBE schema io.zeebe.gossip.protocol (id 5, version 1), do not edit.
+
+#[allow(unused_imports)]
+use unterflow_protocol::io::{CharArray, Data};
+
+#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub enum GossipEventType {
+    #[default]
+    Ping = 0,
+    Ack = 1,
+    PingReq = 2,
+    SyncRequest = 3,
+    SyncResponse = 4,
+}
+
+#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub enum MembershipEventType {
+    #[default]
+    Join = 0,
+    Suspect = 1,
+    Alive = 2,
+    Confirm = 3,
+    Leave = 4,
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub struct GossipEventMembershipEvents {
+    pub event_type: MembershipEventType,
+    pub gossip_epoch: u64,
+    pub gossip_heartbeat: u64,
+    pub port: u16,
+    pub host: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub struct GossipEventCustomEvents {
+    pub sender_gossip_epoch: u64,
+    pub sender_gossip_heartbeat: u64,
+    pub sender_port: u16,
+    pub sender_host: String,
+    pub event_type: String,
+    pub payload: Data,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "0", schema_id = "5", version = "1")]
+pub struct GossipEvent {
+    pub event_type: GossipEventType,
+    pub probe_member_port: u16,
+    pub sender_port: u16,
+    pub membership_events: Vec<GossipEventMembershipEvents>,
+    pub custom_events: Vec<GossipEventCustomEvents>,
+    pub probe_member_host: String,
+    pub sender_host: String,
+}
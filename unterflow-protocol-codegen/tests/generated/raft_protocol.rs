@@ -0,0 +1,151 @@
+// Generated by unterflow-protocol-codegen from SBE schema io.zeebe.raft.protocol (id 4, version 1), do not edit.
+
+#[allow(unused_imports)]
+use unterflow_protocol::io::{CharArray, Data};
+
+#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub enum BooleanType {
+    #[default]
+    False = 0,
+    True = 1,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "0", schema_id = "4", version = "1")]
+pub struct JoinRequest {
+    pub partition_id: u16,
+    pub term: u16,
+    pub port: u16,
+    pub topic_name: String,
+    pub host: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub struct JoinResponseMembers {
+    pub port: u16,
+    pub host: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "1", schema_id = "4", version = "1")]
+pub struct JoinResponse {
+    pub term: u16,
+    pub succeeded: BooleanType,
+    pub configuration_entry_position: u64,
+    pub configuration_entry_term: i32,
+    pub members: Vec<JoinResponseMembers>,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "2", schema_id = "4", version = "1")]
+pub struct LeaveRequest {
+    pub partition_id: u16,
+    pub term: u16,
+    pub port: u16,
+    pub topic_name: String,
+    pub host: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub struct LeaveResponseMembers {
+    pub port: u16,
+    pub host: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "3", schema_id = "4", version = "1")]
+pub struct LeaveResponse {
+    pub term: u16,
+    pub succeeded: BooleanType,
+    pub configuration_entry_position: u64,
+    pub configuration_entry_term: i32,
+    pub members: Vec<LeaveResponseMembers>,
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub struct ConfigurationRequestMembers {
+    pub port: u16,
+    pub host: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "4", schema_id = "4", version = "1")]
+pub struct ConfigurationRequest {
+    pub partition_id: u16,
+    pub term: u16,
+    pub configuration_entry_position: u64,
+    pub configuration_entry_term: i32,
+    pub members: Vec<ConfigurationRequestMembers>,
+    pub topic_name: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "5", schema_id = "4", version = "1")]
+pub struct ConfigurationResponse {
+    pub term: u16,
+    pub succeeded: BooleanType,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "6", schema_id = "4", version = "1")]
+pub struct PollRequest {
+    pub partition_id: u16,
+    pub term: u16,
+    pub last_event_position: u64,
+    pub last_event_term: i32,
+    pub port: u16,
+    pub topic_name: String,
+    pub host: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "7", schema_id = "4", version = "1")]
+pub struct PollResponse {
+    pub term: u16,
+    pub granted: BooleanType,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "8", schema_id = "4", version = "1")]
+pub struct VoteRequest {
+    pub partition_id: u16,
+    pub term: u16,
+    pub last_event_position: u64,
+    pub last_event_term: i32,
+    pub port: u16,
+    pub topic_name: String,
+    pub host: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "9", schema_id = "4", version = "1")]
+pub struct VoteResponse {
+    pub term: u16,
+    pub granted: BooleanType,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "10", schema_id = "4", version = "1")]
+pub struct AppendRequest {
+    pub partition_id: u16,
+    pub term: u16,
+    pub previous_event_position: u64,
+    pub previous_event_term: i32,
+    pub commit_position: u64,
+    pub port: u16,
+    pub topic_name: String,
+    pub host: String,
+    pub data: Data,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasMessageLength)]
+#[message(template_id = "11", schema_id = "4", version = "1")]
+pub struct AppendResponse {
+    pub partition_id: u16,
+    pub term: u16,
+    pub succeeded: BooleanType,
+    pub previous_event_position: u64,
+    pub port: u16,
+    pub topic_name: String,
+    pub host: String,
+}
@@ -0,0 +1,57 @@
+// Generated by unterflow-protocol-codegen from SBE schema io.zeebe.transport (id 0, version 1), do not edit.
+
+#[allow(unused_imports)]
+use unterflow_protocol::io::{CharArray, Data};
+
+#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+#[enum_type = "u16"]
+pub enum DataFrameType {
+    #[default]
+    Message = 0,
+    Padding = 1,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, SbeFlags)]
+pub struct FrameFlags {
+    #[sbe(bit = "7")]
+    pub batch_begin: bool,
+    #[sbe(bit = "6")]
+    pub batch_end: bool,
+    #[sbe(bit = "5")]
+    pub failed: bool,
+}
+
+#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub struct DataFrameHeader {
+    pub length: u32,
+    pub version: u8,
+    pub flags: FrameFlags,
+    pub frame_type: DataFrameType,
+    pub stream_id: u32,
+}
+
+#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+#[enum_type = "u16"]
+pub enum TransportProtocol {
+    #[default]
+    RequestResponse = 0,
+    FullDuplexSingleMessage = 1,
+    ControlMessage = 2,
+}
+
+#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub struct TransportHeader {
+    pub protocol: TransportProtocol,
+}
+
+#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+pub struct RequestResponseHeader {
+    pub request_id: u64,
+}
+
+#[derive(Debug, Default, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength)]
+#[enum_type = "u32"]
+pub enum ControlMessage {
+    #[default]
+    KeepAlive = 0,
+}
@@ -1,335 +1,1083 @@
 #![recursion_limit="128"]
 
 extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
 extern crate syn;
 #[macro_use]
 extern crate quote;
 
 use proc_macro::TokenStream;
-use syn::{Body, ConstExpr, DeriveInput, Ident, Lit, MetaItem, NestedMetaItem, Path, Ty, VariantData};
+use proc_macro2::TokenStream as Tokens;
+use syn::{Attribute, Data, DataEnum, DeriveInput, Error, Expr, ExprLit, Field, Fields, GenericArgument, Generics, Ident, Index, Lit, LitStr, Member,
+          Meta, PathArguments, Result, Type};
 
-#[proc_macro_derive(FromBytes, attributes(enum_type))]
+#[proc_macro_derive(FromBytes, attributes(enum_type, sbe))]
 pub fn derive_from_bytes(input: TokenStream) -> TokenStream {
-    let ast = syn::parse_derive_input(&input.to_string()).expect("parse_derive_input");
+    let ast = parse_macro_input!(input as DeriveInput);
+    expand_from_bytes(&ast).unwrap_or_else(compile_error).into()
+}
+
+fn expand_from_bytes(ast: &DeriveInput) -> Result<Tokens> {
     let name = &ast.ident;
 
-    let expr = match ast.body {
-        Body::Struct(VariantData::Struct(ref body)) => {
-            let fields: Vec<_> = body.iter()
-                .filter_map(|field| field.ident.as_ref())
-                .map(|ident| quote! { #ident: FromBytes::from_bytes(reader)? })
-                .collect();
+    if let Data::Enum(ref data) = ast.data {
+        let ty = enum_type(ast)?;
+        let variants = enum_values(data, "FromBytes")?
+            .into_iter()
+            .map(|(variant, value)| quote! { #value => Ok(#name::#variant) });
+        let name_str = name.to_string();
 
-            quote! {
-                impl FromBytes for #name {
-                    // allow empty implementations, i.e. SingleMessageHeader
-                    #[allow(unused_variables)]
-                    fn from_bytes(reader: &mut ::std::io::Read) -> Result<Self, ::std::io::Error> {
-                        Ok(#name { #(#fields),* })
+        return Ok(quote! {
+            impl ::unterflow_protocol::io::FromReader for #name {
+                fn from_reader<R: ::unterflow_protocol::io::Read + ?Sized>(reader: &mut R) -> Result<Self, ::unterflow_protocol::io::Error> {
+                    let value = <#ty as ::unterflow_protocol::io::FromReader>::from_reader(reader)?;
+
+                    match value as u64 {
+                        #(#variants),*,
+                        _ => Err(::unterflow_protocol::io::unknown_variant(value as u64, #name_str))
                     }
                 }
             }
-        }
-        Body::Enum(ref variants) => {
-            let ty = enum_type(&ast);
+        });
+    }
 
-            let variants: Vec<_> = variants
-                .iter()
-                .enumerate()
-                .map(|(idx, variant)| {
-                    let value = match variant.discriminant {
-                        Some(ConstExpr::Lit(Lit::Int(value, _))) => value,
-                        _ => idx as u64,
-                    };
+    let fields = sbe_fields(struct_fields(ast, "FromBytes")?)?;
+    validate_layout(&fields)?;
 
-                    let unqualified_ident = &variant.ident;
-                    let ident = quote! { #name::#unqualified_ident };
-                    quote! { #value => Ok(#ident) }
-                })
-                .collect();
+    let generics = bounded_generics(&ast.generics, quote! { ::unterflow_protocol::io::FromReader });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-            let name_str = name.as_ref();
+    let reads: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let ident = &field.member;
+            let value = field.read(quote! { reader });
+            quote! { #ident: #value }
+        })
+        .collect();
 
-            quote! {
-                impl FromBytes for #name {
-                    fn from_bytes(reader: &mut ::std::io::Read) -> Result<Self, ::std::io::Error> {
-                        let value = #ty::from_bytes(reader)?;
+    let versioned_reads: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let ident = &field.member;
+            let value = field.since_version_or(field.read(quote! { reader }), quote! { Default::default() });
+            quote! { #ident: #value }
+        })
+        .collect();
 
-                        match value as u64 {
-                            #(#variants),*,
-                            _ => Err(::std::io::Error::new(::std::io::ErrorKind::InvalidData, format!("Unknown variant {} for {}", value, #name_str)))
-                        }
-                    }
-                }
+    // fixed fields are read from the block, whose unknown remainder is skipped before groups and var data
+    let (block_reads, block_values) = block_reads(&fields, |field| {
+        let reader = if field_kind(&field.field.ty) == FieldKind::Fixed {
+            quote! { &mut block }
+        } else {
+            quote! { reader }
+        };
+        field.since_version_or(field.read(reader), quote! { Default::default() })
+    }, quote! { block.finish()?; });
+
+    Ok(quote! {
+        impl #impl_generics ::unterflow_protocol::io::FromReader for #name #ty_generics #where_clause {
+            // allow empty implementations, i.e. SingleMessageHeader
+            #[allow(unused_variables)]
+            fn from_reader<R: ::unterflow_protocol::io::Read + ?Sized>(reader: &mut R) -> Result<Self, ::unterflow_protocol::io::Error> {
+                Ok(#name { #(#reads),* })
             }
         }
-        _ => panic!("#[derive(FromBytes)] can only be used with struct or enum"),
-    };
 
-    expr.to_string().parse().expect("parse quote!")
+        impl #impl_generics ::unterflow_protocol::io::FromReaderVersion for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn from_reader_version<R: ::unterflow_protocol::io::Read + ?Sized>(reader: &mut R, version: u16) -> Result<Self, ::unterflow_protocol::io::Error> {
+                Ok(#name { #(#versioned_reads),* })
+            }
+
+            #[allow(unused_variables, unused_mut)]
+            fn from_reader_block<R: ::unterflow_protocol::io::Read + ?Sized>(reader: &mut R, block_length: u16, version: u16) -> Result<Self, ::unterflow_protocol::io::Error> {
+                let mut block = ::unterflow_protocol::io::BlockReader::new(reader, block_length);
+                #(#block_reads)*
+                Ok(#name { #(#block_values),* })
+            }
+        }
+    })
 }
 
-#[proc_macro_derive(ToBytes, attributes(enum_type))]
+#[proc_macro_derive(ToBytes, attributes(enum_type, sbe))]
 pub fn derive_to_bytes(input: TokenStream) -> TokenStream {
-    let ast = syn::parse_derive_input(&input.to_string()).expect("parse_derive_input");
+    let ast = parse_macro_input!(input as DeriveInput);
+    expand_to_bytes(&ast).unwrap_or_else(compile_error).into()
+}
+
+fn expand_to_bytes(ast: &DeriveInput) -> Result<Tokens> {
     let name = &ast.ident;
 
-    let expr = match ast.body {
-        Body::Struct(VariantData::Struct(ref body)) => {
-            let fields: Vec<_> = body.iter()
-                .filter_map(|field| field.ident.as_ref())
-                .map(|ident| quote! { self.#ident.to_bytes(writer)? })
-                .collect();
+    if let Data::Enum(ref data) = ast.data {
+        let ty = enum_type(ast)?;
+        let variants = enum_values(data, "ToBytes")?
+            .into_iter()
+            .map(|(variant, value)| quote! { #name::#variant => ::unterflow_protocol::io::ToWriter::to_writer(&(#value as #ty), writer) });
 
-            quote! {
-                impl ToBytes for #name {
-                    // allow empty implementations, i.e. SingleMessageHeader
-                    #[allow(unused_variables)]
-                    fn to_bytes(&self, writer: &mut ::std::io::Write) -> Result<(), ::std::io::Error> {
-                        #(#fields);*;
-                        Ok(())
+        return Ok(quote! {
+            impl ::unterflow_protocol::io::ToWriter for #name {
+                fn to_writer<W: ::unterflow_protocol::io::Write + ?Sized>(&self, writer: &mut W) -> Result<(), ::unterflow_protocol::io::Error> {
+                    match *self {
+                        #(#variants),*,
                     }
                 }
             }
+        });
+    }
+
+    let fields = sbe_fields(struct_fields(ast, "ToBytes")?)?;
+
+    let generics = bounded_generics(&ast.generics, quote! { ::unterflow_protocol::io::ToWriter });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let writes: Vec<_> = fields.iter().map(|field| field.write(quote! { to_writer })).collect();
+
+    let vectored_writes: Vec<_> = fields
+        .iter()
+        .map(|field| field.write(quote! { to_writer_vectored }))
+        .collect();
+
+    let versioned_writes: Vec<_> = fields
+        .iter()
+        .map(|field| field.since_version_or(field.write(quote! { to_writer }), quote! {}))
+        .collect();
+
+    let versioned_vectored_writes: Vec<_> = fields
+        .iter()
+        .map(|field| field.since_version_or(field.write(quote! { to_writer_vectored }), quote! {}))
+        .collect();
+
+    Ok(quote! {
+        impl #impl_generics ::unterflow_protocol::io::ToWriter for #name #ty_generics #where_clause {
+            // allow empty implementations, i.e. SingleMessageHeader
+            #[allow(unused_variables)]
+            fn to_writer<W: ::unterflow_protocol::io::Write + ?Sized>(&self, writer: &mut W) -> Result<(), ::unterflow_protocol::io::Error> {
+                #(#writes;)*
+                Ok(())
+            }
+
+            // allow empty implementations, i.e. SingleMessageHeader
+            #[allow(unused_variables)]
+            fn to_writer_vectored<'a>(&'a self, writer: &mut ::unterflow_protocol::io::VectoredWriter<'a>) -> Result<(), ::unterflow_protocol::io::Error> {
+                #(#vectored_writes;)*
+                Ok(())
+            }
         }
-        Body::Enum(ref variants) => {
-            let ty = enum_type(&ast);
 
-            let variants: Vec<_> = variants
-                .iter()
-                .enumerate()
-                .map(|(idx, variant)| {
-                    let value = match variant.discriminant {
-                        Some(ConstExpr::Lit(Lit::Int(value, _))) => value,
-                        _ => idx as u64,
-                    };
+        impl #impl_generics ::unterflow_protocol::io::ToWriterVersion for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn to_writer_version<W: ::unterflow_protocol::io::Write + ?Sized>(&self, writer: &mut W, version: u16) -> Result<(), ::unterflow_protocol::io::Error> {
+                #(#versioned_writes;)*
+                Ok(())
+            }
 
-                    let unqualified_ident = &variant.ident;
-                    let ident = quote! { #name::#unqualified_ident };
-                    quote! { #ident => (#value as #ty).to_bytes(writer) }
-                })
-                .collect();
+            #[allow(unused_variables)]
+            fn to_writer_vectored_version<'a>(&'a self, writer: &mut ::unterflow_protocol::io::VectoredWriter<'a>, version: u16) -> Result<(), ::unterflow_protocol::io::Error> {
+                #(#versioned_vectored_writes;)*
+                Ok(())
+            }
+        }
+    })
+}
+
+#[proc_macro_derive(FromBytesRef, attributes(sbe))]
+pub fn derive_from_bytes_ref(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    expand_from_bytes_ref(&ast).unwrap_or_else(compile_error).into()
+}
+
+fn expand_from_bytes_ref(ast: &DeriveInput) -> Result<Tokens> {
+    let name = &ast.ident;
+    let vis = &ast.vis;
+    let fields = sbe_fields(named_fields(ast, "FromBytesRef")?)?;
 
+    let ref_name = format_ident!("{}Ref", name);
+
+    let borrowed = fields.iter().any(|field| borrowed_ty(&field.field.ty).is_some());
+    let lifetime = if borrowed {
+        quote! { <'a> }
+    } else {
+        quote! {}
+    };
+
+    let definitions: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let ident = &field.member;
+            let vis = &field.field.vis;
+            let ty = borrowed_ty(&field.field.ty).unwrap_or_else(|| {
+                let ty = &field.field.ty;
+                quote! { #ty }
+            });
+            quote! { #vis #ident: #ty }
+        })
+        .collect();
+
+    let reads: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let ident = &field.member;
+            let value = field.read_ref();
+            quote! { #ident: #value }
+        })
+        .collect();
+
+    let versioned_reads: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let ident = &field.member;
+            let value = field.since_version_or(field.read_ref(), quote! { Default::default() });
+            quote! { #ident: #value }
+        })
+        .collect();
+
+    let (block_reads, block_values) = block_reads(&fields, |field| {
+        let value = field.since_version_or(field.read_ref(), quote! { Default::default() });
+        if field_kind(&field.field.ty) == FieldKind::Fixed {
             quote! {
-                impl ToBytes for #name {
-                    fn to_bytes(&self, writer: &mut ::std::io::Write) -> Result<(), ::std::io::Error> {
-                        match *self {
-                            #(#variants),*,
-                        }
-                    }
+                {
+                    let buffer = &mut block;
+                    #value
                 }
             }
+        } else {
+            value
+        }
+    }, quote! {});
+
+    let conversions: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let ident = &field.member;
+            quote! { #ident: message.#ident.into() }
+        })
+        .collect();
+
+    Ok(quote! {
+        #[derive(Debug, PartialEq)]
+        #vis struct #ref_name #lifetime {
+            #(#definitions),*
+        }
+
+        impl<'a> ::unterflow_protocol::io::FromBytesRef<'a> for #ref_name #lifetime {
+            // allow empty implementations, i.e. SingleMessageHeader
+            #[allow(unused_variables)]
+            fn from_bytes_ref(buffer: &mut &'a [u8]) -> Result<Self, ::unterflow_protocol::io::Error> {
+                Ok(#ref_name { #(#reads),* })
+            }
+        }
+
+        impl<'a> #ref_name #lifetime {
+            #[allow(unused_variables)]
+            #vis fn from_bytes_ref_version(buffer: &mut &'a [u8], version: u16) -> Result<Self, ::unterflow_protocol::io::Error> {
+                Ok(#ref_name { #(#versioned_reads),* })
+            }
+
+            /// Decodes the message with the block length of its message header, fixed fields of a
+            /// newer version are skipped.
+            #[allow(unused_variables, unused_mut)]
+            #vis fn from_bytes_ref_block(buffer: &mut &'a [u8], block_length: u16, version: u16) -> Result<Self, ::unterflow_protocol::io::Error> {
+                let mut block = ::unterflow_protocol::io::split_block(buffer, block_length)?;
+                #(#block_reads)*
+                Ok(#ref_name { #(#block_values),* })
+            }
         }
-        _ => panic!("#[derive(FromBytes)] can only be used with struct or enum"),
-    };
 
-    expr.to_string().parse().expect("parse quote!")
+        impl<'a> From<#ref_name #lifetime> for #name {
+            // allow empty implementations, i.e. SingleMessageHeader
+            #[allow(unused_variables)]
+            fn from(message: #ref_name #lifetime) -> Self {
+                #name { #(#conversions),* }
+            }
+        }
+    })
 }
 
-#[proc_macro_derive(HasBlockLength, attributes(enum_type))]
+#[proc_macro_derive(HasBlockLength, attributes(enum_type, sbe))]
 pub fn derive_has_block_length(input: TokenStream) -> TokenStream {
-    let ast = syn::parse_derive_input(&input.to_string()).expect("parse_derive_input");
+    let ast = parse_macro_input!(input as DeriveInput);
+    expand_has_block_length(&ast).unwrap_or_else(compile_error).into()
+}
+
+fn expand_has_block_length(ast: &DeriveInput) -> Result<Tokens> {
     let name = &ast.ident;
 
-    let block_length = match ast.body {
-        Body::Struct(VariantData::Struct(ref body)) => {
-            let mut fields: Vec<_> = body.iter()
-                .filter(|field| match field.ty {
-                            // exclude Vec and Strings from block length
-                            Ty::Path(None, ref path) => {
-                                !path.segments
-                                     .iter()
-                                     .any(|seg| {
-                                              let ref ident = seg.ident;
-                                              ident == "Vec" || ident == "String" || ident == "Data"
-                                          })
-                            }
-                            _ => false,
-                        })
-                .map(|field| {
-                    let ref ty = field.ty;
-                    quote! { #ty::block_length() }
-                })
-                .collect();
+    let generics = bounded_generics(&ast.generics, quote! { ::unterflow_protocol::io::ConstBlockLength });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-            // allow empty implementations, i.e. SingleMessageHeader
-            fields.push(quote! { 0 });
+    if let Data::Enum(_) = ast.data {
+        let ty = enum_type(ast)?;
 
-            quote! {
-                #(#fields)+*
+        return Ok(quote! {
+            impl ::unterflow_protocol::io::HasBlockLength for #name {
+                fn block_length() -> u16 {
+                    <#ty as ::unterflow_protocol::io::HasBlockLength>::block_length()
+                }
             }
-        }
-        Body::Enum(_) => {
-            let ty = enum_type(&ast);
 
-            quote! {
-                ::std::mem::size_of::<#ty>()
+            impl ::unterflow_protocol::io::ConstBlockLength for #name {
+                const BLOCK_LENGTH: u16 = <#ty as ::unterflow_protocol::io::ConstBlockLength>::BLOCK_LENGTH;
             }
-        }
-        _ => panic!("#[derive(HasBlockLength)] can only be used with structs or enums"),
+        });
+    }
+
+    let fields = sbe_fields(struct_fields(ast, "HasBlockLength")?)?;
+
+    // exclude groups and var data from block length, fixed-length arrays are part of the block
+    let fixed: Vec<_> = fields
+        .iter()
+        .filter(|field| field_kind(&field.field.ty) == FieldKind::Fixed)
+        .collect();
+
+    let mut lengths: Vec<_> = fixed
+        .iter()
+        .map(|field| {
+            let ty = &field.field.ty;
+            quote! { <#ty as ::unterflow_protocol::io::ConstBlockLength>::BLOCK_LENGTH as usize }
+        })
+        .collect();
+
+    let mut versioned_lengths: Vec<_> = fixed
+        .iter()
+        .map(|field| {
+            let ty = &field.field.ty;
+            field.since_version_or(quote! { <#ty as ::unterflow_protocol::io::HasBlockLength>::block_length() }, quote! { 0 })
+        })
+        .collect();
+
+    // allow empty implementations, i.e. SingleMessageHeader
+    lengths.push(quote! { 0 });
+    versioned_lengths.push(quote! { 0 });
+
+    let message = format!("block length of {} exceeds u16", name);
+
+    // the length of generic structs is only known per instantiation, so it is checked where the
+    // const is evaluated instead
+    let (block_length, assertion) = if ast.generics.params.is_empty() {
+        (quote! { (#(#lengths)+*) as u16 }, quote! { const _: () = assert!(#(#lengths)+* <= u16::MAX as usize, #message); })
+    } else {
+        let block_length = quote! {
+            {
+                let length = #(#lengths)+*;
+                assert!(length <= u16::MAX as usize, #message);
+                length as u16
+            }
+        };
+        (block_length, quote! {})
     };
 
-    let expr = quote! {
-        impl HasBlockLength for #name {
+    Ok(quote! {
+        impl #impl_generics ::unterflow_protocol::io::HasBlockLength for #name #ty_generics #where_clause {
             fn block_length() -> u16 {
-                #block_length as u16
+                <Self as ::unterflow_protocol::io::ConstBlockLength>::BLOCK_LENGTH
+            }
+
+            #[allow(unused_variables)]
+            fn block_length_version(version: u16) -> u16 {
+                (#(#versioned_lengths)+*) as u16
             }
         }
-    };
 
-    expr.to_string().parse().expect("parse quote!")
+        impl #impl_generics ::unterflow_protocol::io::ConstBlockLength for #name #ty_generics #where_clause {
+            const BLOCK_LENGTH: u16 = #block_length;
+        }
+
+        #assertion
+    })
 }
 
-#[proc_macro_derive(Message, attributes(message, template_id, schema_id, version))]
-pub fn derive_message(input: TokenStream) -> TokenStream {
-    let ast = syn::parse_derive_input(&input.to_string()).expect("parse_derive_input");
+#[proc_macro_derive(SbeFlags, attributes(flags_type, sbe))]
+pub fn derive_sbe_flags(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    expand_sbe_flags(&ast).unwrap_or_else(compile_error).into()
+}
+
+fn expand_sbe_flags(ast: &DeriveInput) -> Result<Tokens> {
     let name = &ast.ident;
+    let ty = named_attr(&ast.attrs, "flags_type")?
+        .map(|value| value.parse::<Ident>())
+        .unwrap_or_else(|| Ok(format_ident!("u8")))?;
 
-    let expr = match ast.body {
-        Body::Struct(_) => {
-            let template_id = template_id(&ast).expect("#[derive(Message)] requires message(template_id) attribute]");
-            let schema_id = schema_id(&ast).expect("#[derive(Message)] requires message(schema_id) attribute");
-            let version = version(&ast).expect("#[derive(Message)] requires message(version) attribute]");
+    let width = match ty.to_string().as_str() {
+        "u8" => 8,
+        "u16" => 16,
+        "u32" => 32,
+        "u64" => 64,
+        _ => return Err(Error::new_spanned(&ty, "#[flags_type] must be one of u8, u16, u32 or u64")),
+    };
 
-            let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let fields = sbe_fields(named_fields(ast, "SbeFlags")?)?;
 
-            quote! {
-                impl #impl_generics Message for #name #ty_generics #where_clause {
-                    fn template_id() -> u16 {
-                        #template_id
-                    }
+    let mut bits = Vec::with_capacity(fields.len());
+    for (idx, field) in fields.iter().enumerate() {
+        if !is_ident(&field.field.ty, "bool") {
+            return Err(Error::new_spanned(&field.field.ty, "#[derive(SbeFlags)] fields must be bool"));
+        }
 
-                    fn schema_id() -> u16 {
-                        #schema_id
-                    }
+        let bit = match field.bit {
+            Some(ref value) => {
+                let bit: u8 = parse_number(value)?;
+                if u32::from(bit) >= width {
+                    return Err(Error::new(value.span(), format!("bit {} exceeds the {} bits of {}", bit, width, ty)));
+                }
+                bit
+            }
+            None => idx as u8,
+        };
 
-                    fn version() -> u16 {
-                        #version
-                    }
+        bits.push((&field.member, bit));
+    }
+
+    let reads: Vec<_> = bits.iter()
+        .map(|&(ident, bit)| quote! { #ident: bits & (1 << #bit) != 0 })
+        .collect();
+
+    let writes: Vec<_> = bits.iter()
+        .map(|&(ident, bit)| quote! { if self.#ident { bits |= 1 << #bit; } })
+        .collect();
+
+    let known_bits: Vec<_> = bits.iter().map(|&(_, bit)| quote! { 1 << #bit }).collect();
 
+    let choices: Vec<_> = fields
+        .iter()
+        .zip(&bits)
+        .map(|(field, &(_, bit))| {
+                 let choice = field.name();
+                 quote! { (#choice, #bit) }
+             })
+        .collect();
+
+    let name_str = name.to_string();
+    let primitive = primitive(&parse_quote!(#ty))?;
+
+    Ok(quote! {
+        impl #name {
+            pub const KNOWN_BITS: #ty = 0 #(| #known_bits)*;
+
+            /// Returns `None` if any bit is set which isn't a field of the flags.
+            pub fn from_bits(bits: #ty) -> Option<Self> {
+                if bits & !Self::KNOWN_BITS == 0 {
+                    Some(#name::from_bits_truncate(bits))
+                } else {
+                    None
                 }
             }
+
+            pub fn from_bits_truncate(bits: #ty) -> Self {
+                #name { #(#reads),* }
+            }
+
+            pub fn bits(&self) -> #ty {
+                let mut bits: #ty = 0;
+                #(#writes)*
+                bits
+            }
+        }
+
+        impl ::unterflow_protocol::io::FromReader for #name {
+            fn from_reader<R: ::unterflow_protocol::io::Read + ?Sized>(reader: &mut R) -> Result<Self, ::unterflow_protocol::io::Error> {
+                let bits = <#ty as ::unterflow_protocol::io::FromReader>::from_reader(reader)?;
+                #name::from_bits(bits).ok_or_else(|| ::unterflow_protocol::io::unknown_bits((bits & !Self::KNOWN_BITS) as u64, #name_str))
+            }
+        }
+
+        impl ::unterflow_protocol::io::ToWriter for #name {
+            fn to_writer<W: ::unterflow_protocol::io::Write + ?Sized>(&self, writer: &mut W) -> Result<(), ::unterflow_protocol::io::Error> {
+                ::unterflow_protocol::io::ToWriter::to_writer(&self.bits(), writer)
+            }
+        }
+
+        impl ::unterflow_protocol::io::HasBlockLength for #name {
+            fn block_length() -> u16 {
+                <#ty as ::unterflow_protocol::io::HasBlockLength>::block_length()
+            }
+        }
+
+        impl ::unterflow_protocol::io::ConstBlockLength for #name {
+            const BLOCK_LENGTH: u16 = <#ty as ::unterflow_protocol::io::ConstBlockLength>::BLOCK_LENGTH;
+        }
+
+        impl ::unterflow_protocol::io::HasMessageLength for #name {
+            fn message_length(&self) -> u32 {
+                <#ty as ::unterflow_protocol::io::HasBlockLength>::block_length() as u32
+            }
+        }
+
+        impl ::unterflow_protocol::schema::Describe for #name {
+            fn describe() -> ::unterflow_protocol::schema::TypeDescriptor {
+                ::unterflow_protocol::schema::TypeDescriptor::Set(::unterflow_protocol::schema::SetDescriptor {
+                    name: #name_str,
+                    encoding: ::unterflow_protocol::schema::Primitive::#primitive,
+                    choices: vec![#(#choices),*],
+                })
+            }
+        }
+    })
+}
+
+#[proc_macro_derive(Message, attributes(message))]
+pub fn derive_message(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    expand_message(&ast).unwrap_or_else(compile_error).into()
+}
+
+fn expand_message(ast: &DeriveInput) -> Result<Tokens> {
+    let name = &ast.ident;
+
+    if let Data::Struct(_) = ast.data {
+    } else {
+        return Err(Error::new_spanned(name, "#[derive(Message)] can only be used with structs"));
+    }
+
+    let mut template_id = None;
+    let mut schema_id = None;
+    let mut version = None;
+
+    for attr in ast.attrs.iter().filter(|attr| attr.path().is_ident("message")) {
+        attr.parse_nested_meta(|meta| {
+            let value: LitStr = meta.value()?.parse()?;
+            let number: u16 = parse_number(&value)?;
+
+            if meta.path.is_ident("template_id") {
+                template_id = Some(number);
+            } else if meta.path.is_ident("schema_id") {
+                schema_id = Some(number);
+            } else if meta.path.is_ident("version") {
+                version = Some(number);
+            } else {
+                return Err(meta.error("expected template_id, schema_id or version"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    let missing = |item: &str| Error::new_spanned(name, format!("#[derive(Message)] requires #[message({} = \"...\")] attribute", item));
+    let template_id = template_id.ok_or_else(|| missing("template_id"))?;
+    let schema_id = schema_id.ok_or_else(|| missing("schema_id"))?;
+    let version = version.ok_or_else(|| missing("version"))?;
+
+    // fields of a later schema version than the message itself could never be encoded
+    let since_version = sbe_fields(struct_fields(ast, "Message")?)?.iter().filter_map(|field| field.since_version).max();
+    let assertion = match since_version {
+        Some(since_version) if ast.generics.params.is_empty() => {
+            let message = format!("#[sbe(since_version)] of {} exceeds its message version", name);
+            quote! { const _: () = assert!(#since_version <= <#name as ::unterflow_protocol::io::ConstMessage>::VERSION, #message); }
         }
-        _ => panic!("#[derive(Message)] can only be used with structs"),
+        _ => quote! {},
     };
 
-    expr.to_string().parse().expect("parse quote!")
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::unterflow_protocol::io::Message for #name #ty_generics #where_clause {
+            fn template_id() -> u16 {
+                <Self as ::unterflow_protocol::io::ConstMessage>::TEMPLATE_ID
+            }
+
+            fn schema_id() -> u16 {
+                <Self as ::unterflow_protocol::io::ConstMessage>::SCHEMA_ID
+            }
+
+            fn version() -> u16 {
+                <Self as ::unterflow_protocol::io::ConstMessage>::VERSION
+            }
+        }
+
+        impl #impl_generics ::unterflow_protocol::io::ConstMessage for #name #ty_generics #where_clause {
+            const TEMPLATE_ID: u16 = #template_id;
+            const SCHEMA_ID: u16 = #schema_id;
+            const VERSION: u16 = #version;
+        }
+
+        #assertion
+    })
 }
 
 #[proc_macro_derive(HasData, attributes(data))]
 pub fn derive_has_data(input: TokenStream) -> TokenStream {
-    let ast = syn::parse_derive_input(&input.to_string()).expect("parse_derive_input");
+    let ast = parse_macro_input!(input as DeriveInput);
+    expand_has_data(&ast).unwrap_or_else(compile_error).into()
+}
+
+fn expand_has_data(ast: &DeriveInput) -> Result<Tokens> {
     let name = &ast.ident;
-    let data = named_attr(&ast, "data").unwrap_or("data".to_string());
-    let data = Ident::from(data);
+    let fields = named_fields(ast, "HasData")?;
 
-    let expr = match ast.body {
-        Body::Struct(_) => {
-            quote! {
-                impl HasData for #name {
-                    fn data(&self) -> &Data {
-                        &self.#data
-                    }
-                }
+    let data = match named_attr(&ast.attrs, "data")? {
+        Some(value) => {
+            let data = value.parse::<Ident>()?;
+            if !fields.iter().any(|field| field.ident.as_ref() == Some(&data)) {
+                return Err(Error::new(value.span(), format!("{} has no field `{}`", name, data)));
             }
+            data
         }
-        _ => panic!("#[derive(Message)] can only be used with structs"),
+        None => format_ident!("data"),
     };
 
-    expr.to_string().parse().expect("parse quote!")
+    Ok(quote! {
+        impl ::unterflow_protocol::io::HasData for #name {
+            fn data(&self) -> &::unterflow_protocol::io::Data {
+                &self.#data
+            }
+        }
+    })
 }
 
-#[proc_macro_derive(HasMessageLength, attributes(enum_type))]
+#[proc_macro_derive(HasMessageLength, attributes(enum_type, sbe))]
 pub fn derive_has_message_length(input: TokenStream) -> TokenStream {
-    let ast = syn::parse_derive_input(&input.to_string()).expect("parse_derive_input");
-    let name = &ast.ident;
+    let ast = parse_macro_input!(input as DeriveInput);
+    expand_has_message_length(&ast).unwrap_or_else(compile_error).into()
+}
 
-    let message_length = match ast.body {
-        Body::Struct(VariantData::Struct(ref body)) => {
-            let mut fields: Vec<_> = body.iter()
-                .map(|field| {
-                    let unqualified_ident = &field.ident;
-                    quote! { self.#unqualified_ident.message_length() }
-                })
-                .collect();
+fn expand_has_message_length(ast: &DeriveInput) -> Result<Tokens> {
+    let name = &ast.ident;
 
-            // allow empty implementations, i.e. SingleMessageHeader
-            fields.push(quote! { 0 });
+    let message_length = if let Data::Enum(_) = ast.data {
+        let ty = enum_type(ast)?;
 
-            quote! {
-                #(#fields)+*
+        quote! {
+            fn message_length(&self) -> u32 {
+                <#ty as ::unterflow_protocol::io::HasBlockLength>::block_length() as u32
             }
         }
-        Body::Enum(_) => {
-            let ty = enum_type(&ast);
+    } else {
+        let fields = sbe_fields(struct_fields(ast, "HasMessageLength")?)?;
 
-            quote! {
-                ::std::mem::size_of::<#ty>() as u32
+        let mut lengths: Vec<_> = fields
+            .iter()
+            .map(|field| {
+                let ident = &field.member;
+                quote! { ::unterflow_protocol::io::HasMessageLength::message_length(&self.#ident) }
+            })
+            .collect();
+
+        let mut versioned_lengths: Vec<_> = fields
+            .iter()
+            .map(|field| {
+                let ident = &field.member;
+                field.since_version_or(quote! { ::unterflow_protocol::io::HasMessageLength::message_length(&self.#ident) }, quote! { 0 })
+            })
+            .collect();
+
+        // allow empty implementations, i.e. SingleMessageHeader
+        lengths.push(quote! { 0 });
+        versioned_lengths.push(quote! { 0 });
+
+        quote! {
+            fn message_length(&self) -> u32 {
+                #(#lengths)+*
+            }
+
+            #[allow(unused_variables)]
+            fn message_length_version(&self, version: u16) -> u32 {
+                #(#versioned_lengths)+*
             }
         }
-        _ => panic!("#[derive(HasMessageLength)] can only be used with structs or enums"),
     };
 
-    let expr = quote! {
-        impl HasMessageLength for #name {
-            fn message_length(&self) -> u32 {
-                #message_length
+    let generics = bounded_generics(&ast.generics, quote! { ::unterflow_protocol::io::HasMessageLength });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::unterflow_protocol::io::HasMessageLength for #name #ty_generics #where_clause {
+            #message_length
+        }
+    })
+}
+
+#[proc_macro_derive(Describe, attributes(enum_type, sbe))]
+pub fn derive_describe(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    expand_describe(&ast).unwrap_or_else(compile_error).into()
+}
+
+fn expand_describe(ast: &DeriveInput) -> Result<Tokens> {
+    let name = &ast.ident;
+    let name_str = name.to_string();
+
+    let descriptor = if let Data::Enum(ref data) = ast.data {
+        let primitive = primitive(&enum_type(ast)?)?;
+        let values: Vec<_> = enum_values(data, "Describe")?
+            .into_iter()
+            .map(|(variant, value)| {
+                     let variant = variant.to_string();
+                     quote! { (#variant, #value) }
+                 })
+            .collect();
+
+        quote! {
+            ::unterflow_protocol::schema::TypeDescriptor::Enum(::unterflow_protocol::schema::EnumDescriptor {
+                name: #name_str,
+                encoding: ::unterflow_protocol::schema::Primitive::#primitive,
+                values: vec![#(#values),*],
+            })
+        }
+    } else {
+        let fields: Vec<_> = sbe_fields(struct_fields(ast, "Describe")?)?
+            .iter()
+            .map(|field| {
+                let field_name = field.name();
+                let ty = &field.field.ty;
+                let since_version = match field.since_version {
+                    Some(version) => quote! { Some(#version) },
+                    None => quote! { None },
+                };
+                let null_value = match field.null {
+                    Some((_, _, ref value)) => quote! { Some(#value) },
+                    None => quote! { None },
+                };
+
+                quote! {
+                    ::unterflow_protocol::schema::FieldDescriptor {
+                        name: #field_name,
+                        ty: <#ty as ::unterflow_protocol::schema::Describe>::describe(),
+                        offset: None,
+                        since_version: #since_version,
+                        null_value: #null_value,
+                    }
+                }
+            })
+            .collect();
+
+        quote! {
+            ::unterflow_protocol::schema::TypeDescriptor::Composite(::unterflow_protocol::schema::CompositeDescriptor::new(#name_str, vec![#(#fields),*]))
+        }
+    };
+
+    let generics = bounded_generics(&ast.generics, quote! { ::unterflow_protocol::schema::Describe });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::unterflow_protocol::schema::Describe for #name #ty_generics #where_clause {
+            fn describe() -> ::unterflow_protocol::schema::TypeDescriptor {
+                #descriptor
+            }
+        }
+    })
+}
+
+struct SbeField<'a> {
+    field: &'a Field,
+    member: Member,
+    // inner type of the `Option` and its null value, see `#[sbe(null)]`
+    null: Option<(&'a Type, Expr, LitStr)>,
+    since_version: Option<u16>,
+    bit: Option<LitStr>,
+}
+
+impl<'a> SbeField<'a> {
+    fn name(&self) -> String {
+        match self.member {
+            Member::Named(ref ident) => ident.to_string(),
+            Member::Unnamed(ref index) => index.index.to_string(),
+        }
+    }
+
+    fn read(&self, reader: Tokens) -> Tokens {
+        match self.null {
+            Some((ty, ref null, _)) => {
+                quote! {
+                    {
+                        let value = <#ty as ::unterflow_protocol::io::FromReader>::from_reader(#reader)?;
+                        let null: #ty = #null;
+                        if value == null { None } else { Some(value) }
+                    }
+                }
+            }
+            None => quote! { ::unterflow_protocol::io::FromReader::from_reader(#reader)? },
+        }
+    }
+
+    fn read_ref(&self) -> Tokens {
+        if self.null.is_some() {
+            self.read(quote! { buffer })
+        } else {
+            quote! { ::unterflow_protocol::io::FromBytesRef::from_bytes_ref(buffer)? }
+        }
+    }
+
+    fn write(&self, method: Tokens) -> Tokens {
+        let ident = &self.member;
+        match self.null {
+            Some((ty, ref null, _)) => {
+                quote! {
+                    match self.#ident {
+                        Some(ref value) => ::unterflow_protocol::io::ToWriter::#method(value, writer)?,
+                        None => {
+                            let null: #ty = #null;
+                            ::unterflow_protocol::io::ToWriter::to_writer(&null, writer)?
+                        }
+                    }
+                }
+            }
+            None => quote! { ::unterflow_protocol::io::ToWriter::#method(&self.#ident, writer)? },
+        }
+    }
+
+    // fields added in a later schema version are only encoded if the target version includes them
+    fn since_version_or(&self, value: Tokens, default: Tokens) -> Tokens {
+        match self.since_version {
+            Some(since_version) => quote! { if version >= #since_version { #value } else { #default } },
+            None => value,
+        }
+    }
+}
+
+// reads the fields into locals in order, the fixed fields are followed by `finish_block`
+fn block_reads<F: Fn(&SbeField) -> Tokens>(fields: &[SbeField], read: F, finish_block: Tokens) -> (Vec<Tokens>, Vec<Tokens>) {
+    let mut reads = vec![];
+    let mut values = vec![];
+    let mut finish_block = Some(finish_block);
+
+    for field in fields {
+        if field_kind(&field.field.ty) != FieldKind::Fixed {
+            reads.extend(finish_block.take());
+        }
+
+        let ident = &field.member;
+        let local = format_ident!("field_{}", field.name());
+        let value = read(field);
+        reads.push(quote! { let #local = #value; });
+        values.push(quote! { #ident: #local });
+    }
+    reads.extend(finish_block);
+
+    (reads, values)
+}
+
+fn sbe_fields(fields: &Fields) -> Result<Vec<SbeField<'_>>> {
+    fields.iter().enumerate().map(|(idx, field)| sbe_field(idx, field)).collect()
+}
+
+fn sbe_field(idx: usize, field: &Field) -> Result<SbeField<'_>> {
+    let mut null = None;
+    let mut since_version = None;
+    let mut bit = None;
+
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("sbe")) {
+        attr.parse_nested_meta(|meta| {
+            let value: LitStr = meta.value()?.parse()?;
+
+            if meta.path.is_ident("null") {
+                let ty = option_ty(&field.ty).ok_or_else(|| Error::new_spanned(&field.ty, "#[sbe(null)] can only be used with Option fields"))?;
+                null = Some((ty, value.parse::<Expr>()?, value));
+            } else if meta.path.is_ident("since_version") {
+                since_version = Some(parse_number(&value)?);
+            } else if meta.path.is_ident("bit") {
+                bit = Some(value);
+            } else {
+                return Err(meta.error("expected null, since_version or bit"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(SbeField {
+        field,
+        member: match field.ident {
+            Some(ref ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(idx)),
+        },
+        null,
+        since_version,
+        bit,
+    })
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+enum FieldKind {
+    Fixed,
+    Group,
+    VarData,
+}
+
+fn field_kind(ty: &Type) -> FieldKind {
+    match *ty {
+        Type::Path(ref path) => {
+            match path.path.segments.last() {
+                Some(segment) if segment.ident == "Vec" => FieldKind::Group,
+                Some(segment) if segment.ident == "String" || segment.ident == "Data" => FieldKind::VarData,
+                _ => FieldKind::Fixed,
             }
         }
+        _ => FieldKind::Fixed,
+    }
+}
+
+// SBE requires all fixed-length fields first, followed by groups and then var data
+fn validate_layout(fields: &[SbeField]) -> Result<()> {
+    let mut previous = FieldKind::Fixed;
+
+    for field in fields {
+        let kind = field_kind(&field.field.ty);
+        if kind < previous {
+            let message = match kind {
+                FieldKind::Fixed => format!("fixed-length field `{}` must precede groups and var data", field.name()),
+                _ => format!("group `{}` must precede var data", field.name()),
+            };
+            return Err(Error::new_spanned(field.field, message));
+        }
+        previous = kind;
+    }
+
+    Ok(())
+}
+
+fn option_ty(ty: &Type) -> Option<&Type> {
+    let segment = match *ty {
+        Type::Path(ref path) => path.path.segments.last()?,
+        _ => return None,
     };
 
-    expr.to_string().parse().expect("parse quote!")
+    if segment.ident != "Option" {
+        return None;
+    }
+
+    match segment.arguments {
+        PathArguments::AngleBracketed(ref arguments) => {
+            match arguments.args.first() {
+                Some(GenericArgument::Type(ty)) => Some(ty),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn borrowed_ty(ty: &Type) -> Option<Tokens> {
+    match *ty {
+        Type::Path(ref path) => {
+            path.path
+                .segments
+                .last()
+                .and_then(|seg| if seg.ident == "String" {
+                              Some(quote! { &'a str })
+                          } else if seg.ident == "Data" {
+                              Some(quote! { &'a [u8] })
+                          } else {
+                              None
+                          })
+        }
+        _ => None,
+    }
+}
+
+fn is_ident(ty: &Type, ident: &str) -> bool {
+    match *ty {
+        Type::Path(ref path) => path.qself.is_none() && path.path.is_ident(ident),
+        _ => false,
+    }
 }
 
-fn as_ty(ty: String) -> Ty {
-    let ident = Ident::from(ty);
-    Ty::Path(None, Path::from(ident))
+fn struct_fields<'a>(ast: &'a DeriveInput, derive: &str) -> Result<&'a Fields> {
+    match ast.data {
+        Data::Struct(ref data) => Ok(&data.fields),
+        _ => Err(Error::new_spanned(&ast.ident, format!("#[derive({})] can only be used with structs", derive))),
+    }
 }
 
-fn enum_type(ast: &DeriveInput) -> Ty {
-    named_attr(ast, "enum_type")
-        .map(|value| as_ty(value))
-        .unwrap_or(as_ty("u8".to_string()))
+fn named_fields<'a>(ast: &'a DeriveInput, derive: &str) -> Result<&'a Fields> {
+    let fields = struct_fields(ast, derive)?;
+    match *fields {
+        Fields::Named(_) => Ok(fields),
+        Fields::Unnamed(ref fields) => Err(Error::new_spanned(fields, format!("#[derive({})] does not support tuple structs", derive))),
+        Fields::Unit => Err(Error::new_spanned(&ast.ident, format!("#[derive({})] does not support unit structs", derive))),
+    }
 }
 
-fn named_attr(ast: &DeriveInput, name: &str) -> Option<String> {
-    ast.attrs
+// every type parameter has to implement the derived trait to encode the fields using it
+fn bounded_generics(generics: &Generics, bound: Tokens) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    generics
+}
+
+fn enum_values<'a>(data: &'a DataEnum, derive: &str) -> Result<Vec<(&'a Ident, u64)>> {
+    data.variants
         .iter()
-        .filter_map(|attr| match attr.value {
-                        MetaItem::NameValue(ref ident, Lit::Str(ref value, _)) if ident == name => Some(value.to_owned()),
-                        _ => None,
-                    })
-        .next()
+        .enumerate()
+        .map(|(idx, variant)| {
+            match variant.fields {
+                Fields::Unit => {}
+                ref fields => return Err(Error::new_spanned(fields, format!("#[derive({})] only supports unit variants", derive))),
+            }
+
+            let value = match variant.discriminant {
+                Some((_, Expr::Lit(ExprLit { lit: Lit::Int(ref value), .. }))) => value.base10_parse()?,
+                Some((_, ref expr)) => return Err(Error::new_spanned(expr, "enum discriminant must be an integer literal")),
+                None => idx as u64,
+            };
+
+            Ok((&variant.ident, value))
+        })
+        .collect()
 }
 
-fn template_id(ast: &DeriveInput) -> Option<u16> {
-    list_attr(ast, "message", "template_id").and_then(|value| value.parse::<u16>().ok())
+// variant of `unterflow_protocol::schema::Primitive` for an integer type
+fn primitive(ty: &Type) -> Result<Ident> {
+    let primitive = match *ty {
+        Type::Path(ref path) if path.qself.is_none() => path.path.get_ident().map(|ident| ident.to_string()),
+        _ => None,
+    };
+
+    let primitive = match primitive.as_deref() {
+        Some("u8") => "UInt8",
+        Some("i8") => "Int8",
+        Some("u16") => "UInt16",
+        Some("i16") => "Int16",
+        Some("u32") => "UInt32",
+        Some("i32") => "Int32",
+        Some("u64") => "UInt64",
+        Some("i64") => "Int64",
+        _ => return Err(Error::new_spanned(ty, "expected an integer type like u8, i16, u32 or i64")),
+    };
+
+    Ok(format_ident!("{}", primitive))
 }
 
-fn schema_id(ast: &DeriveInput) -> Option<u16> {
-    list_attr(ast, "message", "schema_id").and_then(|value| value.parse::<u16>().ok())
+fn enum_type(ast: &DeriveInput) -> Result<Type> {
+    match named_attr(&ast.attrs, "enum_type")? {
+        Some(value) => value.parse(),
+        None => Ok(parse_quote!(u8)),
+    }
 }
 
-fn version(ast: &DeriveInput) -> Option<u16> {
-    list_attr(ast, "message", "version").and_then(|value| value.parse::<u16>().ok())
+// `Error::into_compile_error` emits `::core::compile_error!`, which doesn't resolve in 2015 edition crates
+fn compile_error(error: Error) -> Tokens {
+    error
+        .into_iter()
+        .map(|error| {
+                 let message = error.to_string();
+                 quote_spanned! { error.span() => compile_error!(#message); }
+             })
+        .collect()
 }
 
-fn list_attr(ast: &DeriveInput, name: &str, item: &str) -> Option<String> {
-    ast.attrs
-        .iter()
-        .filter_map(|attr| match attr.value {
-                        MetaItem::List(ref ident, ref values) if ident == name => {
-                            values
-                                .iter()
-                                .filter_map(|attr| match *attr {
-                                                NestedMetaItem::MetaItem(MetaItem::NameValue(ref ident, Lit::Str(ref value, _))) if ident == item => {
-                                                    Some(value.to_owned())
-                                                }
-                                                _ => None,
-                                            })
-                                .next()
-                        }
-                        _ => None,
-                    })
-        .next()
+fn parse_number<N: std::str::FromStr>(value: &LitStr) -> Result<N> {
+    value.value().parse().map_err(|_| {
+        Error::new(value.span(), format!("expected {}, found \"{}\"", std::any::type_name::<N>(), value.value()))
+    })
+}
+
+fn named_attr(attrs: &[Attribute], name: &str) -> Result<Option<LitStr>> {
+    let attr = match attrs.iter().find(|attr| attr.path().is_ident(name)) {
+        Some(attr) => attr,
+        None => return Ok(None),
+    };
+
+    match attr.meta {
+        Meta::NameValue(ref meta) => {
+            match meta.value {
+                Expr::Lit(ExprLit { lit: Lit::Str(ref value), .. }) => Ok(Some(value.clone())),
+                ref value => Err(Error::new_spanned(value, format!("expected #[{} = \"...\"]", name))),
+            }
+        }
+        ref meta => Err(Error::new_spanned(meta, format!("expected #[{} = \"...\"]", name))),
+    }
 }
@@ -2,7 +2,10 @@ extern crate unterflow_protocol;
 #[macro_use]
 extern crate unterflow_protocol_derive;
 
-use unterflow_protocol::io::{Data, FromBytes, HasBlockLength, HasData, Message, ToBytes};
+use unterflow_protocol::io::{CharArray, ConstBlockLength, ConstMessage, Data, FromBytes, FromBytesRef, FromReader, FromReaderVersion, HasBlockLength, HasData, HasMessageLength, Message, ToBytes,
+                             ToWriter, ToWriterVersion};
+use unterflow_protocol::sbe::ToMessageHeader;
+use unterflow_protocol::schema::{CompositeDescriptor, Describe, EnumDescriptor, Primitive, SetDescriptor, TypeDescriptor};
 
 
 #[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength)]
@@ -11,7 +14,7 @@ enum Enum {
     B,
 }
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength)]
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, Describe)]
 #[enum_type = "u32"]
 enum EnumWithType {
     A,
@@ -24,7 +27,7 @@ enum EnumWithCustomValues {
     B = 16,
 }
 
-#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength)]
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, Describe)]
 #[enum_type = "u16"]
 enum EnumWithTypeAndCustomValues {
     A,
@@ -49,6 +52,69 @@ struct Struct {
     data: Data,
 }
 
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef)]
+struct Borrowed {
+    a: u16,
+    b: Enum,
+    c: String,
+    d: Data,
+}
+
+#[derive(Debug, PartialEq, FromBytes, FromBytesRef)]
+struct NotBorrowed {
+    a: u16,
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, FromBytesRef, Describe)]
+struct Optional {
+    a: Option<u16>,
+    #[sbe(null = "0")]
+    b: Option<u32>,
+    #[sbe(null = "EnumWithType::A")]
+    c: Option<EnumWithType>,
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength, FromBytesRef, Describe)]
+struct Versioned {
+    a: u16,
+    #[sbe(since_version = "2")]
+    b: Option<u32>,
+    c: String,
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength, FromBytesRef)]
+struct Arrays {
+    a: [u16; 3],
+    name: CharArray<6>,
+    c: String,
+}
+
+#[derive(Debug, Default, PartialEq, SbeFlags)]
+#[flags_type = "u16"]
+struct Flags {
+    a: bool,
+    #[sbe(bit = "9")]
+    b: bool,
+    c: bool,
+}
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength, Describe)]
+struct PartitionId(u16);
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength, Describe)]
+struct Unit;
+
+#[derive(Debug, PartialEq, FromBytes, ToBytes, HasBlockLength, HasMessageLength, Describe)]
+struct Keyed<K>
+where
+    K: Copy,
+{
+    key: K,
+    partition_id: PartitionId,
+    unit: Unit,
+    name: String,
+}
+
 impl Struct {
     fn test() -> Self {
         Struct {
@@ -119,6 +185,24 @@ fn to_bytes_struct() {
     assert_eq!(expected, buffer);
 }
 
+#[test]
+fn from_reader_struct() {
+    let buffer = Struct::test_bytes();
+    let mut cursor = ::std::io::Cursor::new(&buffer);
+
+    assert_eq!(Struct::test(), Struct::from_reader(&mut cursor).unwrap());
+    assert_eq!(buffer.len() as u64, cursor.position());
+}
+
+#[test]
+fn to_writer_struct() {
+    let mut buffer = vec![];
+
+    Struct::test().to_writer(&mut buffer).unwrap();
+
+    assert_eq!(Struct::test_bytes(), buffer);
+}
+
 #[test]
 fn has_block_length() {
     assert_eq!(1, Enum::block_length());
@@ -139,3 +223,274 @@ fn message_struct() {
     assert_eq!(24, Struct::schema_id());
     assert_eq!(36, Struct::version());
 }
+
+#[test]
+fn from_bytes_ref_struct() {
+    let buffer = [1, 0, 1, 3, 0, 102, 111, 111, 2, 0, 1, 2];
+    let mut reader = &buffer[..];
+
+    let borrowed = BorrowedRef::from_bytes_ref(&mut reader).unwrap();
+    assert_eq!(
+        BorrowedRef {
+            a: 1,
+            b: Enum::B,
+            c: "foo",
+            d: &[1, 2],
+        },
+        borrowed
+    );
+    assert!(reader.is_empty());
+
+    assert_eq!(
+        Borrowed::from_bytes(&mut &buffer[..]).unwrap(),
+        Borrowed::from(borrowed)
+    );
+
+    let mut reader: &[u8] = &[1, 0];
+    assert_eq!(
+        NotBorrowed { a: 1 },
+        NotBorrowed::from(NotBorrowedRef::from_bytes_ref(&mut reader).unwrap())
+    );
+}
+
+#[test]
+fn optional_fields() {
+    let optional = Optional {
+        a: Some(1),
+        b: None,
+        c: Some(EnumWithType::B),
+    };
+    let bytes = vec![1, 0, 0, 0, 0, 0, 1, 0, 0, 0];
+
+    let mut buffer = vec![];
+    optional.to_writer(&mut buffer).unwrap();
+    assert_eq!(bytes, buffer);
+    assert_eq!(optional, Optional::from_reader(&mut &bytes[..]).unwrap());
+    assert_eq!(optional, OptionalRef::from_bytes_ref(&mut &bytes[..]).unwrap().into());
+    assert_eq!(10, Optional::block_length());
+
+    let none = vec![0xff, 0xff, 0, 0, 0, 0, 0, 0, 0, 0];
+    let optional = Optional::from_reader(&mut &none[..]).unwrap();
+    assert_eq!(Optional { a: None, b: None, c: None }, optional);
+
+    let mut buffer = vec![];
+    optional.to_writer(&mut buffer).unwrap();
+    assert_eq!(none, buffer);
+}
+
+#[test]
+fn since_version_fields() {
+    let versioned = Versioned {
+        a: 1,
+        b: Some(2),
+        c: "foo".to_string(),
+    };
+
+    let mut current = vec![];
+    versioned.to_writer_version(&mut current, 2).unwrap();
+    assert_eq!(vec![1, 0, 2, 0, 0, 0, 3, 0, b'f', b'o', b'o'], current);
+    assert_eq!(versioned, Versioned::from_reader_version(&mut &current[..], 2).unwrap());
+    assert_eq!(6, Versioned::block_length_version(2));
+    assert_eq!(11, versioned.message_length_version(2));
+
+    let mut old = vec![];
+    versioned.to_writer_version(&mut old, 1).unwrap();
+    assert_eq!(vec![1, 0, 3, 0, b'f', b'o', b'o'], old);
+    assert_eq!(2, Versioned::block_length_version(1));
+    assert_eq!(7, versioned.message_length_version(1));
+
+    let expected = Versioned { b: None, ..versioned };
+    assert_eq!(expected, Versioned::from_reader_version(&mut &old[..], 1).unwrap());
+    assert_eq!(expected, VersionedRef::from_bytes_ref_version(&mut &old[..], 1).unwrap().into());
+}
+
+#[test]
+fn block_length_skips_unknown_fields() {
+    let versioned = Versioned {
+        a: 1,
+        b: Some(2),
+        c: "foo".to_string(),
+    };
+
+    // a newer version with two bytes of unknown fixed fields
+    let newer = vec![1, 0, 2, 0, 0, 0, 0xaa, 0xbb, 3, 0, b'f', b'o', b'o'];
+    assert_eq!(versioned, Versioned::from_reader_block(&mut &newer[..], 8, 2).unwrap());
+    assert_eq!(versioned, VersionedRef::from_bytes_ref_block(&mut &newer[..], 8, 2).unwrap().into());
+
+    let error = Versioned::from_reader_block(&mut &newer[..], 4, 2).unwrap_err();
+    assert_eq!("Message fields exceed block length", error.to_string());
+    assert!(VersionedRef::from_bytes_ref_block(&mut &newer[..], 4, 2).is_err());
+}
+
+#[test]
+fn array_fields() {
+    let arrays = Arrays {
+        a: [1, 2, 3],
+        name: CharArray::new("foo").unwrap(),
+        c: "bar".to_string(),
+    };
+    let bytes = vec![1, 0, 2, 0, 3, 0, b'f', b'o', b'o', 0, 0, 0, 3, 0, b'b', b'a', b'r'];
+
+    let mut buffer = vec![];
+    arrays.to_writer(&mut buffer).unwrap();
+    assert_eq!(bytes, buffer);
+    assert_eq!(arrays, Arrays::from_reader(&mut &bytes[..]).unwrap());
+    assert_eq!(arrays, ArraysRef::from_bytes_ref(&mut &bytes[..]).unwrap().into());
+    assert_eq!(12, Arrays::block_length());
+    assert_eq!(17, arrays.message_length());
+}
+
+#[test]
+fn sbe_flags() {
+    let flags = Flags {
+        a: true,
+        b: true,
+        c: false,
+    };
+    assert_eq!(0b10_0000_0001, flags.bits());
+    assert_eq!(2, Flags::block_length());
+
+    let mut buffer = vec![];
+    flags.to_writer(&mut buffer).unwrap();
+    assert_eq!(vec![1, 2], buffer);
+    assert_eq!(flags, Flags::from_reader(&mut &buffer[..]).unwrap());
+
+    assert_eq!(0b10_0000_0101, Flags::KNOWN_BITS);
+    assert_eq!(None, Flags::from_bits(0b100 | 0b1000));
+    assert_eq!(Flags { c: true, ..Default::default() }, Flags::from_bits_truncate(0b100 | 0b1000));
+
+    let error = Flags::from_reader(&mut &[0b1100, 0][..]).unwrap_err();
+    assert_eq!("Unknown bits 0x8 for Flags", error.to_string());
+}
+
+#[test]
+fn tuple_unit_and_generic_structs() {
+    let keyed = Keyed {
+        key: 7u64,
+        partition_id: PartitionId(3),
+        unit: Unit,
+        name: "foo".to_string(),
+    };
+    let bytes = vec![7, 0, 0, 0, 0, 0, 0, 0, 3, 0, 3, 0, b'f', b'o', b'o'];
+
+    let mut buffer = vec![];
+    keyed.to_writer(&mut buffer).unwrap();
+    assert_eq!(bytes, buffer);
+    assert_eq!(keyed, Keyed::from_reader(&mut &bytes[..]).unwrap());
+    assert_eq!(10, Keyed::<u64>::block_length());
+    assert_eq!(4, Keyed::<u16>::block_length());
+    assert_eq!(15, keyed.message_length());
+
+    assert_eq!(2, PartitionId::block_length());
+    assert_eq!(0, Unit::block_length());
+    assert_eq!(Unit, Unit::from_reader(&mut &[][..]).unwrap());
+}
+
+// implementations written by hand only need the functions, the consts are optional
+struct HandWritten;
+
+impl HasBlockLength for HandWritten {
+    fn block_length() -> u16 {
+        3
+    }
+}
+
+impl Message for HandWritten {
+    fn template_id() -> u16 {
+        1
+    }
+
+    fn schema_id() -> u16 {
+        2
+    }
+
+    fn version() -> u16 {
+        4
+    }
+}
+
+#[test]
+fn hand_written_impls() {
+    let header = <HandWritten as ToMessageHeader>::message_header();
+    assert_eq!((3, 1, 2, 4), (header.block_length, header.template_id, header.schema_id, header.version));
+}
+
+#[test]
+fn associated_consts() {
+    const BLOCK_LENGTH: u16 = Struct::BLOCK_LENGTH;
+    assert_eq!(Struct::block_length(), BLOCK_LENGTH);
+    assert_eq!(12, Struct::TEMPLATE_ID);
+    assert_eq!(24, Struct::SCHEMA_ID);
+    assert_eq!(36, Struct::VERSION);
+    assert_eq!(10, Keyed::<u64>::BLOCK_LENGTH);
+    assert_eq!(4, EnumWithType::BLOCK_LENGTH);
+}
+
+// the derives must not rely on the traits being in scope at the call site
+mod unqualified {
+    #[derive(Debug, PartialEq, FromBytes, FromBytesRef, ToBytes, HasBlockLength, Message, HasData, HasMessageLength)]
+    #[message(template_id = "1", schema_id = "2", version = "3")]
+    pub struct Scoped {
+        pub key: u64,
+        pub data: ::unterflow_protocol::io::Data,
+    }
+}
+
+#[test]
+fn derives_without_traits_in_scope() {
+    let scoped = unqualified::Scoped {
+        key: 1,
+        data: Data::from(vec![2]),
+    };
+
+    let mut buffer = vec![];
+    scoped.to_writer(&mut buffer).unwrap();
+    assert_eq!(8, unqualified::Scoped::block_length());
+    assert_eq!(11, scoped.message_length());
+    assert_eq!(1, unqualified::Scoped::template_id());
+    assert_eq!(&vec![2], &**scoped.data());
+
+    let borrowed = unqualified::ScopedRef::from_bytes_ref(&mut &buffer[..]).unwrap();
+    assert_eq!(&[2][..], borrowed.data);
+}
+
+fn composite<T: Describe>() -> CompositeDescriptor {
+    match T::describe() {
+        TypeDescriptor::Composite(composite) => composite,
+        other => panic!("expected composite but found {:?}", other),
+    }
+}
+
+#[test]
+fn describe() {
+    assert_eq!(TypeDescriptor::Enum(EnumDescriptor {
+                                        name: "EnumWithTypeAndCustomValues",
+                                        encoding: Primitive::UInt16,
+                                        values: vec![("A", 0), ("B", 16)],
+                                    }),
+               EnumWithTypeAndCustomValues::describe());
+
+    assert_eq!(TypeDescriptor::Set(SetDescriptor {
+                                       name: "Flags",
+                                       encoding: Primitive::UInt16,
+                                       choices: vec![("a", 0), ("b", 9), ("c", 2)],
+                                   }),
+               Flags::describe());
+
+    let optional = composite::<Optional>();
+    let null_values: Vec<_> = optional.fields.iter().map(|field| field.null_value).collect();
+    assert_eq!(vec![None, Some("0"), Some("EnumWithType::A")], null_values);
+
+    let versioned = composite::<Versioned>();
+    let offsets: Vec<_> = versioned.fields.iter().map(|field| field.offset).collect();
+    assert_eq!(vec![Some(0), Some(2), None], offsets);
+    assert_eq!(Some(2), versioned.fields[1].since_version);
+    assert_eq!(TypeDescriptor::Optional(Box::new(TypeDescriptor::Primitive(Primitive::UInt32))), versioned.fields[1].ty);
+    assert_eq!(TypeDescriptor::String, versioned.fields[2].ty);
+
+    let keyed = composite::<Keyed<u64>>();
+    let names: Vec<_> = keyed.fields.iter().map(|field| field.name).collect();
+    assert_eq!(vec!["key", "partition_id", "unit", "name"], names);
+    assert_eq!(Keyed::<u64>::block_length() as usize, keyed.block_length());
+    assert_eq!("0", composite::<PartitionId>().fields[0].name);
+}
@@ -0,0 +1,9 @@
+extern crate unterflow_protocol;
+
+use unterflow_protocol::io::ConstBlockLength;
+
+const LENGTH: u16 = <[u64; 10000] as ConstBlockLength>::BLOCK_LENGTH;
+
+fn main() {
+    println!("{}", LENGTH);
+}
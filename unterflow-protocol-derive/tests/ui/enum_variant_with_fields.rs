@@ -0,0 +1,11 @@
+extern crate unterflow_protocol;
+#[macro_use]
+extern crate unterflow_protocol_derive;
+
+#[derive(ToBytes)]
+enum Type {
+    A,
+    B(u8),
+}
+
+fn main() {}
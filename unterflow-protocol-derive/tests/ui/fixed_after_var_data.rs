@@ -0,0 +1,12 @@
+extern crate unterflow_protocol;
+#[macro_use]
+extern crate unterflow_protocol_derive;
+
+#[derive(FromBytes)]
+struct Request {
+    a: u8,
+    name: String,
+    b: u16,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+extern crate unterflow_protocol;
+#[macro_use]
+extern crate unterflow_protocol_derive;
+
+#[derive(SbeFlags)]
+struct Flags {
+    a: bool,
+    #[sbe(bit = "8")]
+    b: bool,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+extern crate unterflow_protocol;
+#[macro_use]
+extern crate unterflow_protocol_derive;
+
+#[derive(SbeFlags)]
+struct Flags {
+    a: bool,
+    b: u8,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+extern crate unterflow_protocol;
+#[macro_use]
+extern crate unterflow_protocol_derive;
+
+#[derive(HasData)]
+#[data = "payload"]
+struct Request {
+    data: Data,
+}
+
+fn main() {}
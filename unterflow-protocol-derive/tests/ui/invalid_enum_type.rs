@@ -0,0 +1,12 @@
+extern crate unterflow_protocol;
+#[macro_use]
+extern crate unterflow_protocol_derive;
+
+#[derive(FromBytes)]
+#[enum_type(u16)]
+enum Type {
+    A,
+    B,
+}
+
+fn main() {}
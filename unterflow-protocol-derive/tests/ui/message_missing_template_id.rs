@@ -0,0 +1,11 @@
+extern crate unterflow_protocol;
+#[macro_use]
+extern crate unterflow_protocol_derive;
+
+#[derive(Message)]
+#[message(schema_id = "0", version = "1")]
+struct Request {
+    a: u8,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+extern crate unterflow_protocol;
+#[macro_use]
+extern crate unterflow_protocol_derive;
+
+#[derive(FromBytes, Message)]
+#[message(template_id = "1", schema_id = "0", version = "2")]
+struct Request {
+    a: u8,
+    #[sbe(since_version = "3")]
+    b: Option<u8>,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+extern crate unterflow_protocol;
+#[macro_use]
+extern crate unterflow_protocol_derive;
+
+#[derive(FromBytes)]
+struct Request {
+    #[sbe(null = "0")]
+    a: u8,
+}
+
+fn main() {}
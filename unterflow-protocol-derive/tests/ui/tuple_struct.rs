@@ -0,0 +1,8 @@
+extern crate unterflow_protocol;
+#[macro_use]
+extern crate unterflow_protocol_derive;
+
+#[derive(FromBytesRef)]
+struct Tuple(u8, u16);
+
+fn main() {}
@@ -0,0 +1,11 @@
+extern crate unterflow_protocol;
+#[macro_use]
+extern crate unterflow_protocol_derive;
+
+#[derive(FromBytes)]
+struct Request {
+    #[sbe(nul = "0")]
+    a: Option<u8>,
+}
+
+fn main() {}